@@ -0,0 +1,62 @@
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+
+use rustopengl::input::{ InputMap, Binding, Modifiers };
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Action {
+	/// Applies the tool selected in the toolbar.
+	UseTool,
+	PaintRoad,
+	EraseRoad,
+	Pan,
+	SpawnCar,
+	RotateLeft,
+	RotateRight,
+	ToggleProjection,
+	FollowCar,
+	ToggleDebug
+}
+
+pub const ACTIONS: &'static [Action] = &[
+	Action::UseTool, Action::PaintRoad, Action::EraseRoad, Action::Pan, Action::SpawnCar,
+	Action::RotateLeft, Action::RotateRight, Action::ToggleProjection, Action::FollowCar,
+	Action::ToggleDebug
+];
+
+impl Action {
+	/// Name used for the `bind_<name>` config keys.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Action::UseTool => "use_tool",
+			Action::PaintRoad => "paint_road",
+			Action::EraseRoad => "erase_road",
+			Action::Pan => "pan",
+			Action::SpawnCar => "spawn_car",
+			Action::RotateLeft => "rotate_left",
+			Action::RotateRight => "rotate_right",
+			Action::ToggleProjection => "toggle_projection",
+			Action::FollowCar => "follow_car",
+			Action::ToggleDebug => "toggle_debug"
+		}
+	}
+}
+
+pub fn default_bindings() -> InputMap<Action> {
+	let alt = Modifiers { alt: true, .. Modifiers::none() };
+	let shift = Modifiers { shift: true, .. Modifiers::none() };
+
+	let mut map = InputMap::new();
+	map.bind(Action::UseTool, Binding::mouse(MouseButton::Left));
+	map.bind(Action::PaintRoad, Binding::mouse(MouseButton::Left).with(shift));
+	map.bind(Action::EraseRoad, Binding::mouse(MouseButton::Right));
+	map.bind(Action::Pan, Binding::mouse(MouseButton::Middle));
+	map.bind(Action::Pan, Binding::mouse(MouseButton::Left).with(alt));
+	map.bind(Action::SpawnCar, Binding::key(Keycode::Return));
+	map.bind(Action::RotateLeft, Binding::key(Keycode::Q));
+	map.bind(Action::RotateRight, Binding::key(Keycode::E));
+	map.bind(Action::ToggleProjection, Binding::key(Keycode::P));
+	map.bind(Action::FollowCar, Binding::key(Keycode::F));
+	map.bind(Action::ToggleDebug, Binding::key(Keycode::F1));
+	map
+}
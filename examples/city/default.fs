@@ -0,0 +1,56 @@
+#version 330
+out vec4 fragColor;
+
+in DATA {
+	vec3 position;
+	vec3 normal;
+	vec2 uv;
+	vec4 lightPos;
+} fs_in;
+
+uniform vec3 lightDir = vec3(-1.0, -1.0, -1.0);
+uniform vec3 lightColor = vec3(1.0);
+uniform vec3 ambient = vec3(0.3, 0.3, 0.5);
+
+uniform vec4 color = vec4(1.0);
+uniform bool disableTexture = false;
+uniform sampler2D texture0;
+
+uniform bool enableShadows = false;
+uniform sampler2D shadowMap;
+
+float lind(float d, float n, float f) {
+	return (2.0 * n) / (f + n - d * (f - n));
+}
+
+float shadow(vec3 N) {
+	vec3 p = (fs_in.lightPos.xyz / fs_in.lightPos.w) * 0.5 + 0.5;
+	if (p.z > 1.0) return 0.0;
+
+	float bias = max(0.005 * (1.0 - dot(N, -lightDir)), 0.001);
+	vec2 texel = 1.0 / vec2(textureSize(shadowMap, 0));
+
+	// 3x3 PCF
+	float s = 0.0;
+	for (int x = -1; x <= 1; x++) {
+		for (int y = -1; y <= 1; y++) {
+			float d = texture(shadowMap, p.xy + vec2(x, y) * texel).r;
+			s += p.z - bias > d ? 1.0 : 0.0;
+		}
+	}
+	return s / 9.0;
+}
+
+void main() {
+	vec3 N = normalize(fs_in.normal);
+	float nl = min(1.0, max(dot(N, -lightDir), 0.0));
+	if (enableShadows) { nl *= 1.0 - shadow(N); }
+	vec3 diff = vec3(nl) * lightColor + ambient;
+
+	vec4 col = vec4(1.0);
+	if (!disableTexture) { col = texture(texture0, fs_in.uv); }
+
+	// float d = gl_FragCoord.z * 100.0;
+	fragColor = vec4(diff * color.rgb, 1.0) * col;
+	// fragColor = vec4(vec3(d), 1.0);
+}
\ No newline at end of file
@@ -8,18 +8,21 @@ out DATA {
 	vec3 position;
 	vec3 normal;
 	vec2 uv;
+	vec4 lightPos;
 } vs_out;
 
 uniform mat4 projection;
 uniform mat4 view;
 uniform mat4 model;
+uniform mat3 normalMatrix;
+uniform mat4 lightSpace;
 
 void main() {
 	vec4 pos = model * vec4(v_pos, 1.0);
 	gl_Position = projection * view * pos;
 
-	mat3 nmat = mat3(transpose(inverse(model)));
 	vs_out.position = pos.xyz;
-	vs_out.normal = nmat * v_nrm;
+	vs_out.normal = normalMatrix * v_nrm;
 	vs_out.uv = v_uv;
+	vs_out.lightPos = lightSpace * pos;
 }
\ No newline at end of file
@@ -0,0 +1,914 @@
+extern crate sdl2;
+extern crate gl;
+extern crate rand;
+use sdl2::mouse::MouseButton;
+use sdl2::keyboard::Keycode;
+use std::path::Path;
+use std::time::Instant;
+use self::rand::{ Rng, StdRng, SeedableRng };
+
+use rustopengl::primitives;
+use rustopengl::vecmath::*;
+use rustopengl::renderer::*;
+use rustopengl::ui::*;
+use rustopengl::camera::*;
+use rustopengl::tween::{ Tween, Sequence, Ease };
+use rustopengl::scene::{ Scene, NodeId };
+use rustopengl::spatial::SpatialHash;
+use rustopengl::app::Application;
+use rustopengl::input::{ InputMap, Modifiers };
+use logic::*;
+use options::Options;
+use actions::Action;
+
+const CAR_PICK_RADIUS: f32 = 0.25;
+const PANEL_WIDTH: f32 = 200.0;
+const HEADLIGHT_SIZE: f32 = 0.04;
+/// Bounds of the car model, facing -Z
+const CAR_MIN: Vec3 = Vec3 { x: -0.12, y: 0.0, z: -0.17 };
+const CAR_MAX: Vec3 = Vec3 { x: 0.12, y: 0.17, z: 0.22 };
+
+/// What a scene node draws.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Prop {
+	Tile(i32, i32),
+	/// A car body with its tint
+	Car(Vec3),
+	Headlight
+}
+
+#[derive(Debug, Clone)]
+struct Car {
+	pub pos: Vec2,
+	pub dir: Vec2,
+	pub rot: Quat,
+	prev_pos: Vec2,
+	prev_rot: Quat,
+	speed: f32,
+	waypoints: Vec<Vec2>,
+	path: Option<Spline<f32>>,
+	/// Distance driven along `path`
+	travelled: f32,
+	stopped: bool,
+	start_x: i32,
+	start_y: i32,
+	node: NodeId
+}
+
+impl Car {
+	pub fn new<R: Rng>(map: &Map, scene: &mut Scene<Prop>, rng: &mut R, speed: f32) -> Car {
+		let (sx, sy) = map.get_random_road_point(rng);
+		Car::new_at(sx, sy, map, scene, rng, speed)
+	}
+
+	pub fn new_at<R: Rng>(sx: i32, sy: i32, map: &Map, scene: &mut Scene<Prop>, rng: &mut R, speed: f32) -> Car {
+		let ep = map.get_random_road_point(rng);
+
+		let waypoints = map.find_path(sx, sy, ep.0, ep.1)
+						   .into_iter()
+						   .map(|i| Vec2::new(i.0 as f32 + 0.5, i.1 as f32 + 0.5))
+						   .collect();
+		let pos = Vec2::new(sx as f32 + 0.5, sy as f32 + 0.5);
+		let color = Vec3::new(
+			rng.gen_range(0.5f32, 1.0f32),
+			rng.gen_range(0.5f32, 1.0f32),
+			rng.gen_range(0.5f32, 1.0f32)
+		);
+
+		let node = scene.add(Some(Prop::Car(color)));
+		scene.node_mut(node).bounds = Some((CAR_MIN, CAR_MAX));
+		for &x in [-0.07, 0.07].iter() {
+			// The cube model spans (0, 0, -1) to (1, 1, 0); the car faces -Z
+			let light = scene.add_child(node, Some(Prop::Headlight));
+			let l = scene.node_mut(light);
+			l.set_position(Vec3::new(x - HEADLIGHT_SIZE / 2.0, 0.06, -0.15));
+			l.set_scale(Vec3::new(HEADLIGHT_SIZE, HEADLIGHT_SIZE, HEADLIGHT_SIZE));
+			l.bounds = Some((Vec3::new(0.0, 0.0, -1.0), Vec3::new(1.0, 1.0, 0.0)));
+		}
+
+		let mut car = Car {
+			pos: pos,
+			prev_pos: pos,
+			prev_rot: Quat::identity(),
+			dir: Vec2::new(0.0, 0.0),
+			speed: speed,
+			waypoints: waypoints,
+			path: None,
+			travelled: 0.0,
+			stopped: false,
+			start_x: sx,
+			start_y: sy,
+			rot: Quat::identity(),
+			node: node
+		};
+		car.build_path();
+		car
+	}
+
+	pub fn set_destination(&mut self, x: i32, y: i32, map: &Map) {
+		if map.get_bit(x, y) == 0 { return; }
+
+		// Route from the tile the car is on now, not from where its trip began
+		let path = map.find_path(self.pos.x.floor() as i32, self.pos.y.floor() as i32, x, y);
+		// Unreachable, keep going where it was headed
+		if path.is_empty() { return; }
+		self.stopped = false;
+
+		self.waypoints = path.into_iter()
+							.map(|i| Vec2::new(i.0 as f32 + 0.5, i.1 as f32 + 0.5))
+							.collect();
+		self.build_path();
+	}
+
+	fn refresh<R: Rng>(&mut self, map: &Map, rng: &mut R) {
+		let mut lx = 0;
+		let mut ly = 0;
+		if self.waypoints.len() > 0 {
+			let last_w = self.last_waypoint();
+			lx = (last_w.x - 0.5).floor() as i32;
+			ly = (last_w.y - 0.5).floor() as i32;
+			self.start_x = lx;
+			self.start_y = ly;
+		} else {
+			let (x, y) = map.get_random_road_point(rng);
+			lx = x; ly = y;
+		}
+
+		let ep = map.get_random_road_point(rng);
+		// println!("{:?} -> {:?}", (lx, ly), ep);
+
+		let path = map.find_path(lx, ly, ep.0, ep.1);
+
+		self.waypoints = path.into_iter()
+							.map(|i| Vec2::new(i.0 as f32 + 0.5, i.1 as f32 + 0.5))
+							.collect();
+		self.build_path();
+	}
+
+	/// Fits a curve through the waypoints, starting from where the car is now.
+	fn build_path(&mut self) {
+		self.travelled = 0.0;
+		self.path = None;
+		if self.waypoints.is_empty() { return; }
+
+		// The first waypoint is the centre of the tile the car is on, which
+		// may be behind it
+		let points: Vec<Vec3> = Some(self.pos).into_iter()
+			.chain(self.waypoints.iter().skip(1).cloned())
+			.map(|p| Vec3::new(p.x, 0.0, p.y))
+			.collect();
+		self.path = Some(Spline::catmull_rom(&points));
+	}
+
+	pub fn last_waypoint(&self) -> Vec2 {
+		self.waypoints[self.waypoints.len().wrapping_sub(1)]
+	}
+
+	pub fn destination(&self) -> Option<(i32, i32)> {
+		if self.waypoints.is_empty() { return None; }
+		let l = self.last_waypoint();
+		Some((l.x.floor() as i32, l.y.floor() as i32))
+	}
+
+	/// Distance left to drive along the path, in tiles.
+	pub fn remaining_distance(&self) -> f32 {
+		match self.path {
+			Some(ref path) => (path.length() - self.travelled).max(0.0),
+			None => 0.0
+		}
+	}
+
+	/// Ray test against a sphere roughly enclosing the car. Returns the hit distance.
+	pub fn intersect(&self, origin: Vec3, dir: Vec3) -> Option<f32> {
+		let c = Vec3::new(self.pos.x, 0.08, self.pos.y) - origin;
+		let t = c.dot(dir);
+		let d2 = c.dot(c) - t * t;
+		if t > 0.0 && d2 <= CAR_PICK_RADIUS * CAR_PICK_RADIUS { Some(t) } else { None }
+	}
+
+	/// Position blended between the previous and the current tick.
+	pub fn render_pos(&self, alpha: f32) -> Vec2 {
+		self.prev_pos.lerp(self.pos, alpha)
+	}
+
+	/// Moves the car's scene node to its pose blended between ticks.
+	pub fn place(&self, scene: &mut Scene<Prop>, alpha: f32) {
+		let p = self.render_pos(alpha);
+		let node = scene.node_mut(self.node);
+		node.set_position(Vec3::new(p.x, 0.0, p.y));
+		node.set_rotation(self.prev_rot.nlerp(self.rot, alpha));
+	}
+
+	pub fn debug_draw(&self, dd: &mut DebugDraw) {
+		let pos = Vec3::new(self.pos.x, 0.0, self.pos.y);
+		dd.arrow(pos, pos + Vec3::new(self.dir.x, 0.0, self.dir.y) * 0.5, Vec4::new(1.0, 0.2, 0.2, 1.0), 0.0);
+
+		if self.waypoints.is_empty() { return; }
+
+		let path_col = Vec4::new(1.0, 1.0, 1.0, 0.3);
+		for i in 1..self.waypoints.len() {
+			let a = self.waypoints[i - 1];
+			let b = self.waypoints[i];
+			dd.line(Vec3::new(a.x, 0.0, a.y), Vec3::new(b.x, 0.0, b.y), path_col, 0.0);
+		}
+
+		if let Some(ref path) = self.path {
+			let curve_col = Vec4::new(1.0, 1.0, 0.0, 1.0);
+			let n = (path.length() * 8.0).ceil().max(1.0) as usize;
+			let step = path.length() / n as f32;
+			for i in 0..n {
+				dd.line(path.point_at(step * i as f32), path.point_at(step * (i + 1) as f32), curve_col, 0.0);
+			}
+		}
+
+		let f = self.waypoints[0];
+		dd.circle(Vec3::new(f.x, 0.0, f.y), 0.2, Vec4::new(1.0, 0.5, 0.0, 1.0), 0.0);
+
+		let l = self.last_waypoint();
+		dd.circle(Vec3::new(l.x, 0.0, l.y), 0.2, Vec4::new(0.0, 1.0, 0.0, 1.0), 0.0);
+	}
+
+	pub fn update<R: Rng>(&mut self, dt: f32, map: &Map, rng: &mut R) {
+		self.prev_pos = self.pos;
+		self.prev_rot = self.rot;
+
+		if self.path.is_none() { self.stopped = true; }
+		if self.stopped { return; }
+
+		self.travelled += self.speed * dt;
+		let (p, t, overshoot) = {
+			let path = self.path.as_ref().unwrap();
+			(path.point_at(self.travelled), path.tangent_at(self.travelled), self.travelled - path.length())
+		};
+
+		self.pos = Vec2::new(p.x, p.z);
+		self.dir = Vec2::new(t.x, t.z).normalized();
+
+		if self.dir.length() > 0.0 {
+			// The car model faces -Z
+			let forward = Vec3::new(-self.dir.x, 0.0, -self.dir.y);
+			let target = Quat::look_rotation(forward, Vec3::new(0.0, 1.0, 0.0));
+			self.rot = self.rot.slerp(target, (dt * 10.0).min(1.0));
+		}
+
+		if overshoot >= 0.0 {
+			self.refresh(map, rng);
+			// Carry on along the new route so the speed stays constant
+			self.travelled = overshoot;
+		}
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Tool {
+	Road,
+	Erase,
+	Spawn,
+	Select
+}
+
+impl Tool {
+	fn name(&self) -> &'static str {
+		match *self {
+			Tool::Road => "Road",
+			Tool::Erase => "Erase",
+			Tool::Spawn => "Spawn Car",
+			Tool::Select => "Select Car"
+		}
+	}
+}
+
+pub struct Game {
+	pub shader: Shader,
+	pub cursor_tex: Texture,
+	textures: [Texture; 16],
+	pub model: Model,
+	house: Model,
+	cube: Model,
+	pub car: Model,
+	house_tex: Texture,
+	pub car_tex: Texture,
+	dmap: Map,
+	scene: Scene<Prop>,
+	/// Car indices by the tile they're on
+	traffic: SpatialHash<usize>,
+	camera: Camera,
+	cursor_x: i32,
+	cursor_y: i32,
+	mouse_pos: Vec2,
+	mouse_prev_pos: Vec2,
+	viewport: Vec2,
+	cars: Vec<Car>,
+	debug: DebugDraw,
+	show_debug: bool,
+	text: TextRenderer,
+	fps_timer: Instant,
+	frames: u32,
+	fps: u32,
+	ui: Ui,
+	tool: Tool,
+	sim_speed: f32,
+	light: Light,
+	shadows: ShadowMap,
+	post: PostStack,
+	mouse_ray: (Vec3, Vec3),
+	selected: Option<usize>,
+	follow: bool,
+	/// Slides the selected car's panel in from the right edge, 1 is hidden
+	panel_slide: Tween<f32>,
+	/// Scale of the ring around the selected car
+	pulse: Sequence<f32>,
+	rng: StdRng,
+	car_speed: f32,
+	input: InputMap<Action>,
+	drag_action: Option<Action>
+}
+
+impl Drop for Game {
+	fn drop(&mut self) {
+		self.cursor_tex.free();
+		for i in 0..self.textures.len() {
+			self.textures[i].free();
+		}
+		self.model.free();
+		self.house.free();
+		self.cube.free();
+		self.house_tex.free();
+		self.car.free();
+		self.car_tex.free();
+		self.debug.free();
+		self.text.free();
+		self.ui.free();
+		self.shadows.free();
+		self.post.free();
+	}
+}
+
+impl Game {
+	pub fn new(options: Options) -> Game {
+		let vs = include_str!("default.vs");
+		let fs = include_str!("default.fs");
+		
+		let shd = Shader::new();
+		shd.add_shader(vs, gl::VERTEX_SHADER);
+		shd.add_shader(fs, gl::FRAGMENT_SHADER);
+		shd.link();
+
+		let mut post = PostStack::new(1, 1, options.msaa);
+		post.add(PostPass::bloom());
+		post.add(PostPass::tonemap());
+		post.add(PostPass::color_grade(None, 16));
+		post.add(PostPass::fxaa());
+		post.add(PostPass::vignette());
+		post.add(PostPass::gamma());
+		for name in ["Color Grading", "Gamma"].iter() {
+			post.pass_mut(name).unwrap().enabled = false;
+		}
+
+		let mut game = Game {
+			shader: shd,
+			cursor_tex: Texture::new(Path::new("res/cursor.png")),
+			textures: [
+				Texture::new(Path::new("res/road_s.png")), //0
+				Texture::new(Path::new("res/road_s_h.png")), //1
+				Texture::new(Path::new("res/road_c00.png")), //2
+				Texture::new(Path::new("res/road_c10.png")), //3
+				Texture::new(Path::new("res/road_c11.png")), //4
+				Texture::new(Path::new("res/road_c01.png")), //5
+				Texture::new(Path::new("res/road_t_l.png")), //6
+				Texture::new(Path::new("res/road_t_u.png")), //7
+				Texture::new(Path::new("res/road_t_r.png")), //8
+				Texture::new(Path::new("res/road_t_d.png")), //9
+				Texture::new(Path::new("res/road_cross.png")), //10
+				Texture::new(Path::new("res/grass.png")), //11
+				Texture::new(Path::new("res/road_e_l.png")), //12
+				Texture::new(Path::new("res/road_e_u.png")), //13
+				Texture::new(Path::new("res/road_e_r.png")), //14
+				Texture::new(Path::new("res/road_e_d.png")), //15
+			],
+			model: primitives::make_plane(),
+			house: Model::from_file(Path::new("res/house.obj"), true).unwrap(),
+			house_tex: Texture::new(Path::new("res/house_tex.png")),
+			cube: Model::from_file(Path::new("res/cube.obj"), true).unwrap(),
+			car: Model::from_file(Path::new("res/car.obj"), true).unwrap(),
+			car_tex: Texture::new(Path::new("res/car_tex.png")),
+			camera: Camera::new(Vec3::new(options.map.width() as f32 * 0.5, 0.0, options.map.height() as f32 * 0.5)),
+			dmap: options.map,
+			scene: Scene::new(),
+			traffic: SpatialHash::new(1.0),
+			cursor_x: 0,
+			cursor_y: 0,
+			mouse_pos: Vec2::new(0.0, 0.0),
+			mouse_prev_pos: Vec2::new(0.0, 0.0),
+			viewport: Vec2::new(1.0, 1.0),
+			cars: Vec::new(),
+			debug: DebugDraw::new(),
+			show_debug: false,
+			text: TextRenderer::new(Font::new(Path::new("res/DejaVuSans.ttf"), 16.0)),
+			fps_timer: Instant::now(),
+			frames: 0,
+			fps: 0,
+			ui: Ui::new(),
+			tool: Tool::Road,
+			sim_speed: 1.0,
+			light: Light::new(
+				Vec3::new(-1.0, -1.0, -1.0),
+				Vec3::new(1.5, 1.5, 1.4),
+				Vec3::new(0.3, 0.3, 0.5)
+			),
+			shadows: ShadowMap::new(2048),
+			post: post,
+			mouse_ray: (Vec3::zero(), Vec3::new(0.0, -1.0, 0.0)),
+			selected: None,
+			follow: false,
+			panel_slide: Tween::done(1.0),
+			pulse: Sequence::new(1.0)
+				.to(1.25, 0.4, Ease::QuadOut)
+				.to(1.0, 0.4, Ease::QuadIn)
+				.looped(),
+			rng: StdRng::from_seed(&[options.seed][..]),
+			car_speed: options.car_speed,
+			input: options.bindings,
+			drag_action: None
+		};
+
+		for y in 0..game.dmap.height() {
+			for x in 0..game.dmap.width() {
+				let tile = game.scene.add(Some(Prop::Tile(x, y)));
+				let node = game.scene.node_mut(tile);
+				node.set_position(Vec3::new(x as f32, 0.0, y as f32));
+				node.bounds = Some((Vec3::zero(), Vec3::new(1.0, 0.0, 1.0)));
+			}
+		}
+
+		for _ in 0..options.cars {
+			game.spawn_car();
+		}
+		game
+	}
+
+	fn paint_road(&mut self) {
+		if self.dmap.get_bit(self.cursor_x, self.cursor_y) == 0 {
+			self.dmap.set_bit(self.cursor_x, self.cursor_y, 1);
+			self.dmap.solve();
+		}
+	}
+
+	fn erase_road(&mut self) {
+		if self.dmap.get_bit(self.cursor_x, self.cursor_y) == 1 {
+			self.dmap.set_bit(self.cursor_x, self.cursor_y, 0);
+			self.dmap.set(self.cursor_x, self.cursor_y, 11);
+			self.dmap.solve();
+		}
+	}
+
+	fn select_at_cursor(&mut self) {
+		let (origin, dir) = self.mouse_ray;
+		// Only cars near where the ray crosses their height can be hit
+		let t = if dir.y.abs() > 1e-4 { (0.08 - origin.y) / dir.y } else { 0.0 };
+		let p = origin + dir * t;
+		let cars = &self.cars;
+		let hit = self.traffic.query_radius(Vec2::new(p.x, p.z), 1.0).into_iter()
+			.filter_map(|i| cars[i].intersect(origin, dir).map(|t| (i, t)))
+			.fold(None, |best: Option<(usize, f32)>, (i, t)| match best {
+				Some((_, bt)) if bt <= t => best,
+				_ => Some((i, t))
+			});
+
+		match (hit, self.selected_car()) {
+			(Some((i, _)), _) => {
+				if self.selected != Some(i) {
+					self.panel_slide = Tween::new(1.0, 0.0, 0.35, Ease::BackOut);
+				}
+				self.selected = Some(i);
+			},
+			(None, Some(i)) if self.dmap.get_bit(self.cursor_x, self.cursor_y) == 1 => {
+				self.cars[i].set_destination(self.cursor_x, self.cursor_y, &self.dmap);
+			},
+			_ => {
+				self.selected = None;
+				self.follow = false;
+			}
+		}
+	}
+
+	fn apply_tool(&mut self) {
+		match self.tool {
+			Tool::Road => { self.paint_road(); },
+			Tool::Erase => { self.erase_road(); },
+			Tool::Spawn => {
+				// get_bit clamps, so check the cursor is on the map itself
+				if self.dmap.valid(self.cursor_x, self.cursor_y) && self.dmap.get_bit(self.cursor_x, self.cursor_y) == 1 {
+					let car = Car::new_at(self.cursor_x, self.cursor_y, &self.dmap, &mut self.scene, &mut self.rng, self.car_speed);
+					self.add_car(car);
+				}
+			},
+			Tool::Select => { self.select_at_cursor(); }
+		}
+	}
+
+	fn apply_action(&mut self, action: Action) {
+		match action {
+			Action::UseTool => { self.apply_tool(); },
+			Action::PaintRoad => { self.paint_road(); },
+			Action::EraseRoad => { self.erase_road(); },
+			Action::Pan => {},
+			Action::SpawnCar => { self.spawn_car(); },
+			Action::RotateLeft => { self.camera.rotate(-1); },
+			Action::RotateRight => { self.camera.rotate(1); },
+			Action::ToggleProjection => { self.camera.toggle_projection(); },
+			Action::FollowCar => {
+				self.follow = self.selected_car().is_some() && !self.follow;
+				match self.selected_car() {
+					Some(i) if self.follow => {
+						let p = self.cars[i].pos;
+						self.camera.fly_to(Vec3::new(p.x, 0.0, p.y));
+					},
+					_ => {}
+				}
+			},
+			Action::ToggleDebug => { self.show_debug = !self.show_debug; }
+		}
+	}
+
+	fn selected_car(&self) -> Option<usize> {
+		match self.selected {
+			Some(i) if i < self.cars.len() => Some(i),
+			_ => None
+		}
+	}
+
+	fn spawn_car(&mut self) {
+		if self.dmap.has_roads() {
+			let car = Car::new(&self.dmap, &mut self.scene, &mut self.rng, self.car_speed);
+			self.add_car(car);
+		}
+	}
+
+	fn add_car(&mut self, car: Car) {
+		self.traffic.insert(self.cars.len(), car.pos);
+		self.cars.push(car);
+	}
+
+	fn toolbar(&mut self, w: f32) {
+		self.ui.begin();
+		self.ui.begin_panel(8.0, 72.0, 160.0);
+
+		self.ui.label("Tools");
+		for &tool in [Tool::Road, Tool::Erase, Tool::Spawn, Tool::Select].iter() {
+			let mut selected = self.tool == tool;
+			if self.ui.toggle(tool.name(), &mut selected) {
+				self.tool = tool;
+			}
+		}
+
+		let mut count = self.cars.len() as f32;
+		if self.ui.slider(&format!("Cars: {}", self.cars.len()), &mut count, 0.0, 64.0) {
+			let count = count.round() as usize;
+			let start = count.min(self.cars.len());
+			for (i, car) in self.cars.drain(start..).enumerate() {
+				self.scene.remove(car.node);
+				self.traffic.remove(start + i);
+			}
+			if self.selected_car().is_none() {
+				self.selected = None;
+				self.follow = false;
+			}
+			while self.cars.len() < count && self.dmap.has_roads() {
+				self.spawn_car();
+			}
+		}
+
+		let label = format!("Speed: x{:.1}", self.sim_speed);
+		self.ui.slider(&label, &mut self.sim_speed, 0.0, 4.0);
+
+		self.ui.toggle("Debug", &mut self.show_debug);
+
+		self.ui.label("Post FX");
+		for pass in self.post.passes.iter_mut() {
+			self.ui.toggle(&pass.name, &mut pass.enabled);
+		}
+
+		self.ui.end_panel();
+
+		if let Some(i) = self.selected_car() {
+			let (speed, origin, dest, remaining, route_len) = {
+				let car = &self.cars[i];
+				(car.speed, (car.start_x, car.start_y), car.destination(), car.remaining_distance(), car.waypoints.len())
+			};
+			// Stopped cars keep their speed, they just don't use it
+			let rate = if self.cars[i].stopped { 0.0 } else { speed * self.sim_speed };
+			let nearby = self.traffic.query_radius(self.cars[i].pos, 2.0).len() - 1;
+
+			let x = w - PANEL_WIDTH - 8.0 + self.panel_slide.value() * (PANEL_WIDTH + 8.0);
+			self.ui.begin_panel(x, 8.0, PANEL_WIDTH);
+			self.ui.label(&format!("Car #{}", i));
+			self.ui.label(&format!("Speed: {:.1} tiles/s", speed));
+			self.ui.label(&format!("Origin: {}, {}", origin.0, origin.1));
+			match dest {
+				Some((x, y)) => self.ui.label(&format!("Destination: {}, {}", x, y)),
+				None => self.ui.label("Destination: none")
+			}
+			self.ui.label(&format!("Route: {} tiles total", route_len));
+			// The font only has ASCII, so no em dash
+			if rate > 0.0 {
+				self.ui.label(&format!("ETA: {:.1}s", remaining / rate));
+			} else {
+				self.ui.label("ETA: -");
+			}
+			self.ui.label(&format!("Nearby: {} cars", nearby));
+			self.ui.toggle("Follow (F)", &mut self.follow);
+			self.ui.end_panel();
+		}
+
+		self.ui.end(&mut self.text);
+	}
+
+	fn pick_ray(&self) -> (Vec3, Vec3) {
+		let viewport = Vec4::new(0.0, 0.0, self.viewport.x, self.viewport.y);
+		let (viewmat, proj) = (self.camera.view(), self.camera.projection());
+		let a = self.mouse_pos.extend(0.0).unproject(viewport, viewmat, proj);
+		let b = self.mouse_pos.extend(1.0).unproject(viewport, viewmat, proj);
+		(a, (b - a).normalized())
+	}
+
+	fn pick_ground(&self) -> Vec3 {
+		let (origin, dir) = self.mouse_ray;
+		if dir.y.abs() <= 1e-6 { return origin; }
+		origin + dir * (-origin.y / dir.y)
+	}
+
+	/// Re-picks the hovered tile. Input handlers call this before acting so
+	/// the result depends only on the camera state at that tick, which keeps
+	/// replays deterministic.
+	fn update_cursor(&mut self) {
+		self.mouse_ray = self.pick_ray();
+		let p = self.pick_ground();
+		self.cursor_x = p.x.floor() as i32;
+		self.cursor_y = p.z.floor() as i32;
+	}
+}
+
+impl Application for Game {
+	fn on_init(&mut self, w: f32, h: f32) {
+		GL!(Enable(gl::DEPTH_TEST));
+		GL!(Enable(gl::BLEND));
+		GL!(BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
+		GL!(FrontFace(gl::CCW));
+
+		self.on_resize(w, h);
+		self.camera.set_bounds(
+			Vec2::zero(),
+			Vec2::new(self.dmap.width() as f32, self.dmap.height() as f32)
+		);
+	}
+
+	fn on_resize(&mut self, w: f32, h: f32) {
+		// Minimized windows report a zero-sized client area
+		if w < 1.0 || h < 1.0 { return; }
+
+		GL!(Viewport(0, 0, w as i32, h as i32));
+		self.viewport = Vec2::new(w, h);
+		self.post.resize(w as i32, h as i32);
+		self.camera.set_viewport(w, h);
+	}
+
+	fn on_mouse_click(&mut self, button: MouseButton, x: f32, y: f32, mods: Modifiers) {
+		// Only the left button works widgets, but no press over the UI reaches the map
+		let captured = if button == MouseButton::Left {
+			self.ui.mouse_down(x, y)
+		} else {
+			self.ui.mouse_move(x, y);
+			self.ui.wants_mouse()
+		};
+		if captured {
+			// Also ends a drag with another button, so it can't carry on under the UI
+			self.drag_action = None;
+			return;
+		}
+
+		self.mouse_pos = Vec2::new(x, y);
+		self.mouse_prev_pos = self.mouse_pos;
+		self.update_cursor();
+
+		self.drag_action = self.input.mouse_action(button, mods);
+		if let Some(action) = self.drag_action {
+			self.apply_action(action);
+		}
+	}
+
+	fn on_mouse_release(&mut self, button: MouseButton) {
+		if button == MouseButton::Left && self.ui.mouse_up() { return; }
+		self.drag_action = None;
+	}
+
+	fn on_mouse_move(&mut self, x: f32, y: f32) {
+		self.mouse_pos.x = x;
+		self.mouse_pos.y = y;
+		self.ui.mouse_move(x, y);
+	}
+
+	fn on_mouse_drag(&mut self, _button: MouseButton, x: f32, y: f32) {
+		// Presses captured by the UI start no action; drags that began on the
+		// map keep going when the cursor crosses a panel
+		if self.drag_action.is_none() { return; }
+		self.update_cursor();
+
+		match self.drag_action {
+			Some(Action::Pan) => {
+				self.camera.pan(x - self.mouse_prev_pos.x, y - self.mouse_prev_pos.y);
+				self.follow = false;
+
+				self.mouse_prev_pos.x = x;
+				self.mouse_prev_pos.y = y;
+			},
+			Some(Action::UseTool) if self.tool == Tool::Road || self.tool == Tool::Erase => {
+				self.apply_tool();
+			},
+			Some(Action::PaintRoad) => { self.paint_road(); },
+			Some(Action::EraseRoad) => { self.erase_road(); },
+			_ => {}
+		}
+	}
+
+	fn on_mouse_wheel(&mut self, amount: i32) {
+		if self.ui.wants_mouse() { return; }
+		self.camera.zoom(amount as f32);
+	}
+
+	fn on_key_press(&mut self, key: Keycode, mods: Modifiers) {
+		if let Some(action) = self.input.key_action(key, mods) {
+			self.update_cursor();
+			self.apply_action(action);
+		}
+	}
+
+	fn on_update(&mut self, dt: f32) {
+		self.camera.update(dt);
+		self.panel_slide.update(dt);
+		self.pulse.update(dt);
+		match self.selected_car() {
+			Some(i) if self.follow => {
+				let p = self.cars[i].pos;
+				self.camera.track(Vec3::new(p.x, 0.0, p.y), dt);
+			},
+			_ => {}
+		}
+
+		let dt = dt * self.sim_speed;
+		for (i, car) in self.cars.iter_mut().enumerate() {
+			car.update(dt, &self.dmap, &mut self.rng);
+			self.traffic.insert(i, car.pos);
+		}
+		self.debug.update(dt);
+	}
+
+	fn on_render(&mut self, w: f32, h: f32, alpha: f32) {
+		let proj = self.camera.projection();
+		let viewmat = self.camera.view();
+
+		for car in self.cars.iter() {
+			car.place(&mut self.scene, alpha);
+		}
+
+		// Shadow pass, the ground only receives
+		let map_max = Vec3::new(self.dmap.width() as f32, 1.0, self.dmap.height() as f32);
+		self.shadows.fit(&self.light, proj * viewmat, Vec3::zero(), map_max);
+		{
+			let frustum = Frustum::from_matrix(self.shadows.light_space);
+			let shd = self.shadows.begin();
+			let (car, cube) = (&self.car, &self.cube);
+			self.scene.draw_culled(&frustum, |prop, model| {
+				let mesh = match *prop {
+					Prop::Tile(..) => return,
+					Prop::Car(_) => car,
+					Prop::Headlight => cube
+				};
+				shd.set_model(model);
+				mesh.draw(gl::TRIANGLES);
+			});
+		}
+		self.shadows.end();
+
+		self.post.begin();
+		GL!(ClearColor(0.1_f32, 0.08_f32, 0.2_f32, 1.0_f32));
+		GL!(Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT));
+
+		self.shader.bind();
+		self.shader.get("projection").unwrap().set(proj);
+		self.shader.get("view").unwrap().set(viewmat);
+		match self.shader.get("texture0") {
+			Some(uniform) => { uniform.set(0); },
+			None => {}
+		}
+		self.light.apply(&mut self.shader);
+		self.shadows.bind(1);
+		self.shader.get("shadowMap").unwrap().set(1);
+		self.shader.get("lightSpace").unwrap().set(self.shadows.light_space);
+		self.shader.get("enableShadows").unwrap().set(1);
+		self.shader.get("disableTexture").unwrap().set(0);
+		self.shader.get("color").unwrap().set(Vec4::new(1.0, 1.0, 1.0, 1.0));
+
+		let stats = {
+			let shader = &mut self.shader;
+			let (textures, dmap, plane) = (&self.textures, &self.dmap, &self.model);
+			let (car, car_tex, cube) = (&self.car, &self.car_tex, &self.cube);
+
+			// Tiles come first and mostly share textures, so only rebind on changes
+			let mut last_tile = -1;
+			self.scene.draw_culled(&Frustum::from_matrix(proj * viewmat), |prop, model| {
+				shader.set_model(model);
+				match *prop {
+					Prop::Tile(x, y) => {
+						let tile = dmap.get(x, y);
+						if tile != last_tile {
+							last_tile = tile;
+							textures[tile as usize].bind(0);
+							shader.get("color").unwrap().set(Vec4::new(1.0, 1.0, 1.0, 1.0));
+						}
+						plane.draw(gl::TRIANGLES);
+					},
+					Prop::Car(color) => {
+						last_tile = -1;
+						car_tex.bind(0);
+						shader.get("color").unwrap().set(color.extend(1.0));
+						car.draw(gl::TRIANGLES);
+					},
+					Prop::Headlight => {
+						last_tile = -1;
+						shader.get("disableTexture").unwrap().set(1);
+						shader.get("color").unwrap().set(Vec4::new(1.0, 0.95, 0.6, 1.0));
+						cube.draw(gl::TRIANGLES);
+						shader.get("disableTexture").unwrap().set(0);
+					}
+				}
+			})
+		};
+
+		// self.house_tex.bind(0);
+		// self.shader.get("model").unwrap().set(Mat4::translation(Vec3::new(0.0, 0.0, 0.0)));
+		// self.house.draw(gl::TRIANGLES);
+
+		// Calculate cursor pos in world space
+		self.update_cursor();
+		let cur_pos = Vec3::new(self.cursor_x as f32, 0.0, self.cursor_y as f32);
+
+		if !self.ui.wants_mouse() {
+			self.cursor_tex.bind(0);
+			self.shader.get("color").unwrap().set(Vec4::new(0.0, 0.3, 0.8, 1.0));
+			self.shader.set_model(Mat4::translation(cur_pos));
+
+			GL!(Disable(gl::DEPTH_TEST));
+			self.model.draw(gl::TRIANGLES);
+			GL!(Enable(gl::DEPTH_TEST));
+		}
+
+		self.shader.unbind();
+
+		self.post.end();
+
+		if self.show_debug {
+			for (i, car) in self.cars.iter().enumerate() {
+				car.debug_draw(&mut self.debug);
+				self.text.billboard(
+					&format!("#{}", i),
+					Vec3::new(car.pos.x, 0.5, car.pos.y), 0.01,
+					Vec4::new(1.0, 1.0, 1.0, 1.0)
+				);
+			}
+			self.text.billboard(
+				&format!("{}, {} ({} cars)", self.cursor_x, self.cursor_y, self.traffic.at(self.cursor_x, self.cursor_y).len()),
+				Vec3::new(cur_pos.x + 0.5, 0.1, cur_pos.z + 0.5), 0.01,
+				Vec4::new(0.6, 0.9, 1.0, 1.0)
+			);
+		}
+		if let Some(i) = self.selected_car() {
+			let car = &self.cars[i];
+			let highlight = Vec4::new(1.0, 0.8, 0.1, 1.0);
+			let p = car.render_pos(alpha);
+			self.debug.oriented_box(self.scene.node(car.node).world(), CAR_MIN, CAR_MAX, highlight, 0.0);
+			self.debug.circle(Vec3::new(p.x, 0.01, p.y), CAR_PICK_RADIUS * self.pulse.value(), highlight, 0.0);
+			if let Some((x, y)) = car.destination() {
+				self.debug.wire_box(Vec3::new(x as f32, 0.0, y as f32), Vec3::new(x as f32 + 1.0, 0.05, y as f32 + 1.0), highlight, 0.0);
+			}
+		}
+		self.debug.render(proj, viewmat);
+
+		self.frames += 1;
+		if self.fps_timer.elapsed().as_secs() >= 1 {
+			self.fps = self.frames;
+			self.frames = 0;
+			self.fps_timer = Instant::now();
+		}
+
+		let mut hud = format!("FPS: {}\nCars: {}\nTool: {}", self.fps, self.cars.len(), self.tool.name());
+		if self.show_debug {
+			hud += &format!("\nDrawn: {} / Culled: {}", stats.drawn, stats.culled);
+		}
+		self.text.text(&hud, Vec2::new(8.0, 8.0), Vec4::new(1.0, 1.0, 1.0, 1.0));
+
+		self.toolbar(w);
+		self.ui.render(w, h);
+		self.text.render(proj, viewmat, w, h);
+	}
+}
@@ -0,0 +1,279 @@
+extern crate rand;
+extern crate num;
+use std::cmp::{ min, max };
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use self::rand::Rng;
+
+pub struct Map {
+	bits: Vec<u8>,
+	map: Vec<i32>,
+	width: i32,
+	height: i32
+}
+
+impl Map {
+	pub fn new(width: i32, height: i32) -> Map {
+		let mut map = Vec::new();
+		let mut bits = Vec::new();
+		map.resize((width * height) as usize, 11i32);
+		bits.resize((width * height) as usize, 0u8);
+		Map { map, bits, width, height }
+	}
+
+	/// Loads a map drawn as text, one row per line: `#` is road, `.` is grass.
+	pub fn load(path: &Path) -> Result<Map, String> {
+		let mut source = String::new();
+		File::open(path)
+			.and_then(|mut f| f.read_to_string(&mut source))
+			.map_err(|e| format!("{}: {}", path.display(), e))?;
+
+		let rows: Vec<&str> = source.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).collect();
+		if rows.is_empty() {
+			return Err(format!("{}: map is empty", path.display()));
+		}
+
+		let width = rows[0].len() as i32;
+		let height = rows.len() as i32;
+		let mut map = Map::new(width, height);
+		for (y, row) in rows.iter().enumerate() {
+			if row.len() as i32 != width {
+				return Err(format!("{}: row {} is {} tiles wide, expected {}", path.display(), y + 1, row.len(), width));
+			}
+			for (x, c) in row.chars().enumerate() {
+				match c {
+					'#' => map.set_bit(x as i32, y as i32, 1),
+					'.' => {},
+					_ => return Err(format!("{}: row {}: unexpected '{}'", path.display(), y + 1, c))
+				}
+			}
+		}
+		map.solve();
+		Ok(map)
+	}
+
+	fn lim_x(&self, x: i32) -> i32 {
+		min(self.width-1, max(x, 0))
+	}
+
+	fn lim_y(&self, y: i32) -> i32 {
+		min(self.height-1, max(y, 0))
+	}
+
+	pub fn solve(&mut self) {
+		for y in 0..self.height {
+			for x in 0..self.width {
+				if self.get_bit(x, y) == 0 { continue; }
+				self.solve_one(x, y);
+			}
+		}
+	}
+
+	pub fn has_roads(&self) -> bool {
+		self.bits.iter().any(|&b| b != 0)
+	}
+
+	pub fn valid(&self, x: i32, y: i32) -> bool {
+		x >= 0 && x < self.width && y >= 0 && y < self.height
+	}
+
+	fn get_neighbors(&self, x: i32, y: i32) -> Vec<(i32, i32, u8)> {
+		let mut vec = Vec::new();
+		if self.valid(x-1, y) {
+			vec.push((x-1, y, self.get_bit(x-1, y)));
+		}
+		if self.valid(x, y-1) {
+			vec.push((x, y-1, self.get_bit(x, y-1)));
+		}
+		if self.valid(x+1, y) {
+			vec.push((x+1, y, self.get_bit(x+1, y)));
+		}
+		if self.valid(x, y+1) {
+			vec.push((x, y+1, self.get_bit(x, y+1)));
+		}
+		vec
+	}
+
+	pub fn get_tile(&self, x: i32, y: i32) -> i32 {
+		let s = [
+			self.get_bit(x, y-1),
+			self.get_bit(x+1, y),
+			self.get_bit(x, y+1),
+			self.get_bit(x-1, y)
+		];
+
+		let bf = s[3] * 8 + s[2] * 4 + s[1] * 2 + s[0];
+		match bf {
+			0 => { 0 },
+			1 => { 13 },
+			2 => { 14 },
+			3 => { 3 },
+			4 => { 15 },
+			5 => { 0 },
+			6 => { 4 },
+			7 => { 8 },
+			8 => { 12 },
+			9 => { 2 },
+			10 => { 1 },
+			11 => { 7 },
+			12 => { 5 },
+			13 => { 6 },
+			14 => { 9 },
+			15 => { 10 },
+			_ => { 11 }
+		}
+	}
+
+	pub fn solve_one(&mut self, x: i32, y: i32) -> i32 {
+		let solved = self.get_tile(x, y);
+		self.set(x, y, solved);
+		solved
+	}
+
+	pub fn get_bit(&self, x: i32, y: i32) -> u8 {
+		self.bits[(self.lim_x(x) + self.lim_y(y) * self.width) as usize]
+	}
+
+	pub fn set_bit(&mut self, x: i32, y: i32, val: u8) {
+		let lx = self.lim_x(x);
+		let ly = self.lim_y(y);
+		self.bits[(lx + ly * self.width) as usize] = val;
+	}
+
+	pub fn get(&self, x: i32, y: i32) -> i32 {
+		self.map[(self.lim_x(x) + self.lim_y(y) * self.width) as usize]
+	}
+
+	pub fn set(&mut self, x: i32, y: i32, val: i32) {
+		let lx = self.lim_x(x);
+		let ly = self.lim_y(y);
+		self.map[(lx + ly * self.width) as usize] = val;
+	}
+
+	/// Picks a road tile uniformly. Panics when `has_roads` is false.
+	pub fn get_random_road_point<R: Rng>(&self, rng: &mut R) -> (i32, i32) {
+		let roads: Vec<usize> = (0..self.bits.len()).filter(|&i| self.bits[i] != 0).collect();
+		let i = *rng.choose(&roads).expect("the map has no roads") as i32;
+		(i % self.width, i / self.width)
+	}
+
+	/// Road tiles from the start to the end, or an empty path when the end
+	/// can't be reached.
+	pub fn find_path(&self, sx: i32, sy: i32, ex: i32, ey: i32) -> Vec<(i32, i32)> {
+		if !self.valid(sx, sy) { return Vec::new(); }
+
+		let mut seen: Vec<bool> = Vec::new();
+		seen.resize((self.width * self.height) as usize, false);
+		
+		let mut path = Vec::new();
+		path.push((sx, sy));
+
+		// Every tile is pushed at most once, so `seen` bounds the search
+		loop {
+			// Backtracked all the way out of the start's road network
+			let (cx, cy) = match path.last() {
+				Some(&p) => p,
+				None => return path
+			};
+			seen[(cx + cy * self.width) as usize] = true;
+
+			let neighs = self.get_neighbors(cx, cy);
+			let mut i = 0;
+			for &(x, y, val) in neighs.iter() {
+				if x == ex && y == ey {
+					path.push((x, y));
+					return path;
+				}
+				if !seen[(x + y * self.width) as usize] && val == 1 {
+					path.push((x, y));
+					break;
+				} else {
+					i += 1;
+				}
+			}
+
+			if i == neighs.len() {
+				path.pop();
+			}
+		}
+
+	}
+
+	pub fn width(&self) -> i32 { self.width }
+	pub fn height(&self) -> i32 { self.height }
+
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(rows: &[&str]) -> Map {
+		let mut map = Map::new(rows[0].len() as i32, rows.len() as i32);
+		for (y, row) in rows.iter().enumerate() {
+			for (x, c) in row.chars().enumerate() {
+				if c == '#' { map.set_bit(x as i32, y as i32, 1); }
+			}
+		}
+		map
+	}
+
+	fn assert_connected(map: &Map, path: &[(i32, i32)], start: (i32, i32), end: (i32, i32)) {
+		assert_eq!(path.first(), Some(&start));
+		assert_eq!(path.last(), Some(&end));
+		for w in path.windows(2) {
+			let ((ax, ay), (bx, by)) = (w[0], w[1]);
+			assert_eq!((ax - bx).abs() + (ay - by).abs(), 1, "{:?} -> {:?}", w[0], w[1]);
+			assert_eq!(map.get_bit(bx, by), 1);
+		}
+	}
+
+	#[test]
+	fn unreachable_destination_has_no_path() {
+		let map = parse(&[
+			"##.##",
+			"#...#",
+			"##.##"
+		]);
+		assert!(map.find_path(0, 0, 4, 2).is_empty());
+		assert!(map.find_path(-1, 0, 1, 0).is_empty());
+	}
+
+	#[test]
+	fn finds_long_winding_paths() {
+		// Dead-end branches off every bend make the search backtrack a lot
+		let map = parse(&[
+			"##########",
+			".#.#.#.#.#",
+			"##########",
+			"#.#.#.#.#.",
+			"##########",
+			".#.#.#.#.#",
+			"##########",
+			"#.#.#.#.#.",
+			"##########",
+			"##########"
+		]);
+		let path = map.find_path(0, 0, 9, 9);
+		assert_connected(&map, &path, (0, 0), (9, 9));
+
+		let snake = parse(&[
+			"#########",
+			"........#",
+			"#########",
+			"#........",
+			"#########",
+			"........#",
+			"#########"
+		]);
+		let path = snake.find_path(0, 0, 0, 6);
+		assert_connected(&snake, &path, (0, 0), (0, 6));
+		assert_eq!(path.len(), 9 * 4 + 3);
+
+		// The search walks the whole row left and back before it tries the
+		// neighbour on the right, taking about twice as many steps as tiles
+		let row = parse(&["##########"]);
+		assert_eq!(row.find_path(8, 0, 9, 0), vec![(8, 0), (9, 0)]);
+	}
+}
@@ -0,0 +1,39 @@
+#[macro_use]
+extern crate rustopengl;
+extern crate sdl2;
+extern crate gl;
+#[macro_use]
+extern crate log;
+
+use std::env;
+use std::process;
+
+mod logic;
+mod actions;
+mod options;
+mod game;
+use game::*;
+use options::*;
+
+use rustopengl::{ app, logger };
+
+fn main() {
+	if env::args().any(|a| a == "--help" || a == "-h") {
+		println!("{}", USAGE);
+		return;
+	}
+
+	// Set up before the runner does, so option errors are logged too
+	logger::init(log::LogLevelFilter::Info);
+
+	let options = match Options::from_args(env::args().skip(1)) {
+		Ok(options) => options,
+		Err(e) => {
+			error!("{}", e);
+			process::exit(2);
+		}
+	};
+	info!("Using seed {}.", options.seed);
+
+	app::run(options.settings.clone(), move || Game::new(options));
+}
@@ -0,0 +1,186 @@
+extern crate rand;
+
+use std::path::{ Path, PathBuf };
+use self::rand::{ thread_rng, Rng };
+
+use rustopengl::app::Settings;
+use rustopengl::config::{ Config, ConfigError };
+use rustopengl::input::{ InputMap, Binding };
+use rustopengl::replay::Replay;
+use logic::Map;
+use actions::{ self, Action, ACTIONS };
+
+pub const DEFAULT_CONFIG: &'static str = "city.cfg";
+
+const KEYS: &'static [&'static str] = &[
+	"config", "record", "replay", "title", "resolution", "fullscreen", "vsync", "msaa",
+	"map_size", "map_file", "cars", "car_speed", "seed", "tick_rate", "bind_*"
+];
+
+pub const USAGE: &'static str = "\
+Options can be set in city.cfg as `key = value` or on the command line as --key=value:
+  config       config file to read (default city.cfg)
+  record       file to record all input to, for bug reports
+  replay       recording to play back; its recorded options replace all others
+  title        window title
+  resolution   window size, e.g. 1280x720
+  fullscreen   true/false
+  vsync        true/false
+  msaa         0, 2, 4, 8 or 16 samples
+  map_size     empty map size, e.g. 16x16
+  map_file     text map to load, `#` for road and `.` for grass
+  cars         number of cars spawned at startup
+  car_speed    car speed in tiles per second
+  seed         RNG seed, random when omitted
+  tick_rate    simulation ticks per second
+  bind_<name>  comma separated bindings for an action, e.g. bind_pan = MouseMiddle, Alt+MouseLeft
+               actions: use_tool, paint_road, erase_road, pan, spawn_car, rotate_left,
+               rotate_right, toggle_projection, follow_car, toggle_debug";
+
+pub struct Options {
+	pub settings: Settings,
+	/// Samples for the scene target; the window itself isn't multisampled
+	pub msaa: i32,
+	pub map: Map,
+	pub cars: usize,
+	pub car_speed: f32,
+	pub seed: usize,
+	pub bindings: InputMap<Action>
+}
+
+impl Options {
+	/// Reads the config file (if any) and applies command-line overrides on top.
+	pub fn from_args<I: Iterator<Item = String>>(args: I) -> Result<Options, ConfigError> {
+		let mut overrides = Config::new();
+		overrides.parse_args(args)?;
+
+		let mut replay = None;
+		let config = match (overrides.get_str("replay"), overrides.get_str("config")) {
+			(Some(path), _) => {
+				let r = Replay::load(Path::new(path)).map_err(|e| overrides.error("replay", &e))?;
+				let mut config = Config::new();
+				config.parse(&r.header, path)?;
+				replay = Some(r);
+				config
+			},
+			(None, path) => {
+				let mut config = match path {
+					Some(path) => Config::load(Path::new(path))?,
+					None if Path::new(DEFAULT_CONFIG).exists() => Config::load(Path::new(DEFAULT_CONFIG))?,
+					None => Config::new()
+				};
+				config.merge(&overrides);
+				config
+			}
+		};
+
+		let mut options = Options::from_config(&config)?;
+		options.settings.replay = replay;
+
+		if let Some(path) = overrides.get_str("record") {
+			// Pin the seed so the recording replays with the same cars
+			let mut header = config.clone();
+			header.set("seed", &options.seed.to_string(), "recording");
+			options.settings.record = Some(PathBuf::from(path));
+			options.settings.record_header = header.write(&["config", "record", "replay"]);
+		}
+
+		Ok(options)
+	}
+
+	pub fn from_config(config: &Config) -> Result<Options, ConfigError> {
+		config.check_keys(KEYS)?;
+
+		let mut settings = Settings::default();
+		settings.title = config.get("title", settings.title)?;
+
+		if let Some((w, h)) = config.get_size("resolution")? {
+			if w < 320 || h < 240 || w > 16384 || h > 16384 {
+				return Err(config.error("resolution", "must be between 320x240 and 16384x16384"));
+			}
+			settings.width = w;
+			settings.height = h;
+		}
+
+		settings.fullscreen = config.get("fullscreen", settings.fullscreen)?;
+		settings.vsync = config.get("vsync", settings.vsync)?;
+
+		let msaa = config.get("msaa", 8)?;
+		if ![0, 2, 4, 8, 16].contains(&msaa) {
+			return Err(config.error("msaa", "must be 0, 2, 4, 8 or 16"));
+		}
+
+		settings.tick_rate = config.get("tick_rate", settings.tick_rate)?;
+		if !(settings.tick_rate >= 1.0 && settings.tick_rate <= 1000.0) {
+			return Err(config.error("tick_rate", "must be between 1 and 1000"));
+		}
+
+		let map = match (config.get_size("map_size")?, config.get_str("map_file")) {
+			(Some(_), Some(_)) => {
+				return Err(config.error("map_size", "cannot be combined with `map_file`"));
+			},
+			(_, Some(path)) => {
+				Map::load(Path::new(path)).map_err(|e| config.error("map_file", &e))?
+			},
+			(Some((w, h)), None) => {
+				if w < 2 || h < 2 || w > 256 || h > 256 {
+					return Err(config.error("map_size", "must be between 2x2 and 256x256"));
+				}
+				Map::new(w as i32, h as i32)
+			},
+			(None, None) => Map::new(16, 16)
+		};
+
+		let cars = config.get("cars", 0usize)?;
+		if cars > 1024 {
+			return Err(config.error("cars", "must be at most 1024"));
+		}
+		if cars > 0 && !map.has_roads() {
+			return Err(config.error("cars", "the map has no roads to spawn cars on"));
+		}
+
+		let car_speed = config.get("car_speed", 1.5f32)?;
+		if !(car_speed > 0.0 && car_speed <= 20.0) {
+			return Err(config.error("car_speed", "must be above 0 and at most 20"));
+		}
+
+		let seed = match config.get_opt("seed")? {
+			Some(seed) => seed,
+			None => thread_rng().gen()
+		};
+
+		Ok(Options {
+			settings: settings,
+			msaa: msaa,
+			map: map,
+			cars: cars,
+			car_speed: car_speed,
+			seed: seed,
+			bindings: Options::load_bindings(config)?
+		})
+	}
+
+	/// Starts from the default bindings; a `bind_<action>` key replaces all
+	/// bindings of that action, and an empty value leaves it unbound.
+	fn load_bindings(config: &Config) -> Result<InputMap<Action>, ConfigError> {
+		let mut map = actions::default_bindings();
+		for key in config.keys() {
+			if !key.starts_with("bind_") { continue; }
+
+			let action = match ACTIONS.iter().find(|a| a.name() == &key[5..]) {
+				Some(&a) => a,
+				None => return Err(config.error(&key, "unknown action"))
+			};
+
+			map.unbind(action);
+			let value = config.get_str(&key).unwrap();
+			for b in value.split(',').map(|b| b.trim()).filter(|b| !b.is_empty()) {
+				match b.parse::<Binding>() {
+					Ok(binding) => map.bind(action, binding),
+					Err(e) => return Err(config.error(&key, &e))
+				}
+			}
+		}
+		Ok(map)
+	}
+}
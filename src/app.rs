@@ -0,0 +1,262 @@
+extern crate sdl2;
+extern crate gl;
+
+use std::path::PathBuf;
+use sdl2::event::{ Event, WindowEvent };
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use input::Modifiers;
+use gldebug::{ self, DebugFilter };
+use logger;
+use replay::{ Input, Recorder, Replay };
+
+/// Callbacks driven by `run`. Everything except `on_render` is optional.
+pub trait Application {
+	fn on_init(&mut self, _w: f32, _h: f32) {}
+	fn on_resize(&mut self, _w: f32, _h: f32) {}
+	fn on_key_press(&mut self, _key: Keycode, _mods: Modifiers) {}
+	fn on_mouse_click(&mut self, _button: MouseButton, _x: f32, _y: f32, _mods: Modifiers) {}
+	fn on_mouse_release(&mut self, _button: MouseButton) {}
+	fn on_mouse_move(&mut self, _x: f32, _y: f32) {}
+	fn on_mouse_drag(&mut self, _button: MouseButton, _x: f32, _y: f32) {}
+	fn on_mouse_wheel(&mut self, _amount: i32) {}
+
+	/// Called `tick_rate` times per second with a fixed `dt`.
+	fn on_update(&mut self, _dt: f32) {}
+
+	/// `alpha` is how far the frame lies between the last two updates, in [0, 1).
+	fn on_render(&mut self, w: f32, h: f32, alpha: f32);
+}
+
+#[derive(Debug, Clone)]
+pub struct Settings {
+	pub title: String,
+	pub width: u32,
+	pub height: u32,
+	pub resizable: bool,
+	pub fullscreen: bool,
+	pub vsync: bool,
+	/// Simulation ticks per second.
+	pub tick_rate: f32,
+	/// Longest frame time fed into the simulation, so a stall doesn't snowball.
+	pub max_frame_time: f32,
+	/// Records every input and frame to this file.
+	pub record: Option<PathBuf>,
+	/// Written at the top of recordings, e.g. the options needed to replay them.
+	pub record_header: String,
+	/// Plays a recording back instead of taking user input, then resumes
+	/// live input once it runs out.
+	pub replay: Option<Replay>,
+	/// Routes GL debug output into the log; `None` leaves it off.
+	pub gl_debug: Option<DebugFilter>
+}
+
+impl Default for Settings {
+	fn default() -> Settings {
+		Settings {
+			title: String::from("Test"),
+			width: 800,
+			height: 600,
+			resizable: true,
+			fullscreen: false,
+			vsync: true,
+			tick_rate: 60.0,
+			max_frame_time: 0.25,
+			record: None,
+			record_header: String::new(),
+			replay: None,
+			gl_debug: Some(DebugFilter::default())
+		}
+	}
+}
+
+/// Creates the window and GL 3.3 core context, builds the application with
+/// `create` once the context is current, and runs the main loop until quit.
+pub fn run<A, F>(mut settings: Settings, create: F) where A: Application, F: FnOnce() -> A {
+	logger::init(::log::LogLevelFilter::Info);
+
+	let sdl = sdl2::init().unwrap();
+	let video = sdl.video().unwrap();
+	let mut time = sdl.timer().unwrap();
+
+	let gl_attr = video.gl_attr();
+
+	gl_attr.set_context_profile(sdl2::video::GLProfile::Core);
+	if settings.gl_debug.is_some() {
+		gl_attr.set_context_flags().debug().set();
+	}
+	gl_attr.set_context_version(3, 3);
+
+	// Anti-aliasing happens in multisampled render targets, the window
+	// itself only receives the resolved image
+	let mut builder = video.window(&settings.title, settings.width, settings.height);
+	builder.opengl();
+	if settings.resizable {
+		builder.resizable();
+	}
+	if settings.fullscreen {
+		builder.fullscreen_desktop();
+	}
+
+	let window = match builder.build() {
+		Ok(window) => window,
+		Err(e) => panic!("Could not create a window: {}", e)
+	};
+	let glc = match window.gl_create_context() {
+		Ok(glc) => glc,
+		Err(e) => panic!("Could not create an OpenGL context: {}", e)
+	};
+	window.gl_make_current(&glc).unwrap();
+
+	gl::load_with(|name| video.gl_get_proc_address(name) as *const _);
+
+	if let Some(ref filter) = settings.gl_debug {
+		if !gldebug::install(filter) {
+			info!("GL debug output is not available on this driver.");
+		}
+	}
+
+	if !video.gl_set_swap_interval(if settings.vsync { 1 } else { 0 }) {
+		warn!("Could not set swap interval {}.", if settings.vsync { 1 } else { 0 });
+	}
+
+	let mut event_pump = sdl.event_pump().unwrap();
+
+	let sz = window.size();
+	let mut w = sz.0 as f32;
+	let mut h = sz.1 as f32;
+
+	// Replays keep the recorded viewport so picking lands on the same tiles
+	let mut replay = settings.replay.take();
+	if let Some(ref r) = replay {
+		w = r.size.0;
+		h = r.size.1;
+		info!("Replaying {} frames.", r.frame_count());
+	}
+
+	let mut recorder = match settings.record {
+		Some(ref path) => match Recorder::create(path, &settings.record_header) {
+			Ok(mut r) => {
+				r.init(w, h);
+				Some(r)
+			},
+			Err(e) => {
+				warn!("Could not record to {}: {}", path.display(), e);
+				None
+			}
+		},
+		None => None
+	};
+
+	let mut app = create();
+	app.on_init(w, h);
+
+	let time_step = 1.0 / settings.tick_rate;
+	let frequency = time.performance_frequency() as f64;
+	let mut last_time = time.performance_counter();
+	let mut accum = 0f32;
+	let mut tick = 0u64;
+	let mut button_down = false;
+	let mut mouse_button = MouseButton::Left;
+	let mut mods = Modifiers::none();
+	let mut inputs = Vec::new();
+
+	'running: loop {
+		let current = time.performance_counter();
+		let delta = ((current - last_time) as f64 / frequency) as f32;
+		last_time = current;
+		accum += delta.min(settings.max_frame_time);
+
+		inputs.clear();
+		for event in event_pump.poll_iter() {
+			match event {
+				Event::Quit {..} => {
+					break 'running
+				},
+				Event::Window { win_event: WindowEvent::SizeChanged(nw, nh), .. } => {
+					inputs.push(Input::Resize(nw as f32, nh as f32));
+				},
+				Event::KeyDown { keycode, keymod, .. } => {
+					mods = Modifiers::from_sdl(keymod);
+					match keycode {
+						Some(k) => { inputs.push(Input::KeyPress(k, mods)); },
+						None => {}
+					}
+				},
+				Event::KeyUp { keymod, .. } => {
+					mods = Modifiers::from_sdl(keymod);
+				},
+				Event::MouseButtonDown { mouse_btn, x, y, .. } => {
+					mouse_button = mouse_btn;
+					inputs.push(Input::MouseClick(mouse_btn, x as f32, y as f32, mods));
+					button_down = true;
+				},
+				Event::MouseButtonUp { mouse_btn, .. } => {
+					inputs.push(Input::MouseRelease(mouse_btn));
+					button_down = false;
+				},
+				Event::MouseWheel { y, .. } => {
+					inputs.push(Input::MouseWheel(y));
+				},
+				Event::MouseMotion { x, y, .. } => {
+					inputs.push(Input::MouseMove(x as f32, y as f32));
+					if button_down {
+						inputs.push(Input::MouseDrag(mouse_button, x as f32, y as f32));
+					}
+				}
+				_ => {}
+			}
+		}
+
+		let (ticks, alpha) = match replay.as_mut().map(|r| r.next_frame().cloned()) {
+			Some(Some(frame)) => {
+				// Live input is ignored while a replay is running
+				inputs = frame.inputs;
+				(frame.ticks, frame.alpha)
+			},
+			other => {
+				if other.is_some() {
+					info!("Replay finished at tick {}.", tick);
+					replay = None;
+					accum = 0.0;
+
+					let sz = window.size();
+					inputs.push(Input::Resize(sz.0 as f32, sz.1 as f32));
+				}
+
+				let mut ticks = 0;
+				while accum >= time_step {
+					accum -= time_step;
+					ticks += 1;
+				}
+				(ticks, accum / time_step)
+			}
+		};
+
+		for input in inputs.iter() {
+			if let Input::Resize(nw, nh) = *input {
+				w = nw;
+				h = nh;
+			}
+			if let Some(ref mut r) = recorder {
+				r.input(tick, input);
+			}
+			input.send(&mut app);
+		}
+
+		for _ in 0..ticks {
+			app.on_update(time_step);
+			tick += 1;
+		}
+
+		app.on_render(w, h, alpha);
+		if let Some(ref mut r) = recorder {
+			r.frame(ticks, alpha);
+		}
+
+		window.gl_swap_window();
+	}
+
+	// Release GL resources while the context is still alive
+	drop(app);
+}
@@ -0,0 +1,173 @@
+use std::f32::consts::PI;
+use vecmath::*;
+use tween::{ Tween, Ease };
+
+const MIN_SCALE: f32 = 2.0;
+const MAX_SCALE: f32 = 20.0;
+const ZOOM_STEP: f32 = 0.9;
+const SMOOTHING: f32 = 10.0;
+const FOV: f32 = 45.0;
+const ROTATE_TIME: f32 = 0.4;
+const ZOOM_TIME: f32 = 0.25;
+const FLY_TIME: f32 = 0.8;
+
+/// Orbiting camera looking down at a focus point on the ground. Zoom and
+/// rotation are set as targets and tweened towards every update.
+pub struct Camera {
+	focus: Vec3,
+	pitch: f32,
+	yaw: f32,
+	target_yaw: f32,
+	yaw_tween: Tween<f32>,
+	scale: f32,
+	target_scale: f32,
+	scale_tween: Tween<f32>,
+	flight: Option<Tween<Vec3>>,
+	aspect: f32,
+	bounds_min: Vec2,
+	bounds_max: Vec2,
+	pub perspective: bool
+}
+
+impl Camera {
+	pub fn new(focus: Vec3) -> Camera {
+		let yaw = -45.0f32.to_radians();
+		Camera {
+			focus: focus,
+			pitch: -32.264f32.to_radians(),
+			yaw: yaw,
+			target_yaw: yaw,
+			yaw_tween: Tween::done(yaw),
+			scale: 5.0,
+			target_scale: 5.0,
+			scale_tween: Tween::done(5.0),
+			flight: None,
+			aspect: 1.0,
+			bounds_min: Vec2::new(-1e9, -1e9),
+			bounds_max: Vec2::new(1e9, 1e9),
+			perspective: false
+		}
+	}
+
+	pub fn set_viewport(&mut self, w: f32, h: f32) {
+		self.aspect = w / h;
+	}
+
+	/// Limits the focus point to the given ground rectangle.
+	pub fn set_bounds(&mut self, min: Vec2, max: Vec2) {
+		self.bounds_min = min;
+		self.bounds_max = max;
+		let f = self.focus;
+		self.focus_on(f);
+	}
+
+	pub fn focus(&self) -> Vec3 { self.focus }
+
+	fn clamp(&self, p: Vec3) -> Vec3 {
+		Vec3::new(
+			p.x.max(self.bounds_min.x).min(self.bounds_max.x),
+			0.0,
+			p.z.max(self.bounds_min.y).min(self.bounds_max.y)
+		)
+	}
+
+	pub fn focus_on(&mut self, p: Vec3) {
+		self.focus = self.clamp(p);
+	}
+
+	/// Glides the focus over to `p`.
+	pub fn fly_to(&mut self, p: Vec3) {
+		let to = self.clamp(p);
+		self.flight = Some(Tween::new(self.focus, to, FLY_TIME, Ease::CubicInOut));
+	}
+
+	pub fn flying(&self) -> bool {
+		self.flight.is_some()
+	}
+
+	/// Eases the focus towards `p`, used to follow moving objects. During a
+	/// `fly_to` the flight is redirected to land on `p` instead.
+	pub fn track(&mut self, p: Vec3, dt: f32) {
+		let to = self.clamp(p);
+		if let Some(ref mut flight) = self.flight {
+			flight.to = to;
+			return;
+		}
+
+		let t = (dt * SMOOTHING * 0.5).min(1.0);
+		let f = self.focus + (p - self.focus) * t;
+		self.focus_on(f);
+	}
+
+	/// Moves the focus by a mouse drag delta given in pixels.
+	pub fn pan(&mut self, dx: f32, dy: f32) {
+		let speed = 0.01 * self.scale / 5.0;
+		let d = Vec2::new(dx * speed, dy * speed).rotate(-self.yaw);
+		let f = self.focus - Vec3::new(d.x, 0.0, d.y);
+		self.flight = None;
+		self.focus_on(f);
+	}
+
+	/// Zooms in for positive `steps` and out for negative ones.
+	pub fn zoom(&mut self, steps: f32) {
+		self.target_scale = (self.target_scale * ZOOM_STEP.powf(steps)).max(MIN_SCALE).min(MAX_SCALE);
+		self.scale_tween = Tween::new(self.scale, self.target_scale, ZOOM_TIME, Ease::QuadOut);
+	}
+
+	/// Rotates around the focus in 90 degree steps.
+	pub fn rotate(&mut self, steps: i32) {
+		self.target_yaw += steps as f32 * PI / 2.0;
+		self.yaw_tween = Tween::new(self.yaw, self.target_yaw, ROTATE_TIME, Ease::CubicOut);
+	}
+
+	pub fn toggle_projection(&mut self) {
+		self.perspective = !self.perspective;
+	}
+
+	pub fn update(&mut self, dt: f32) {
+		self.yaw = self.yaw_tween.update(dt);
+		self.scale = self.scale_tween.update(dt);
+
+		let landed = match self.flight {
+			Some(ref mut flight) => {
+				self.focus = flight.update(dt);
+				flight.finished()
+			},
+			None => false
+		};
+		if landed { self.flight = None; }
+	}
+
+	/// Orientation of the camera in world space: yaw around the up axis, then
+	/// pitch. The view rotation is its inverse.
+	pub fn orientation(&self) -> Quat {
+		Quat::from_euler(-self.pitch, self.yaw, 0.0)
+	}
+
+	fn distance(&self) -> f32 {
+		self.scale / (FOV.to_radians() / 2.0).tan()
+	}
+
+	pub fn view(&self) -> Mat4 {
+		let orbit = self.orientation().conjugate().to_mat4() *
+			Mat4::scaling(Vec3::new(1.0, -1.0, 1.0)) *
+			Mat4::translation(-self.focus);
+
+		if self.perspective {
+			Mat4::translation(Vec3::new(0.0, 0.0, -self.distance())) * orbit
+		} else {
+			orbit
+		}
+	}
+
+	pub fn projection(&self) -> Mat4 {
+		let s = self.scale;
+		if self.perspective {
+			// The view flips Y, undo it here like the ortho projection does
+			Mat4::scaling(Vec3::new(1.0, -1.0, 1.0)) *
+				Mat4::perspective(FOV.to_radians(), self.aspect, 0.1, self.distance() * 4.0)
+		} else {
+			Mat4::ortho(-s * self.aspect, s * self.aspect, s, -s, -100.0, 100.0)
+		}
+	}
+}
\ No newline at end of file
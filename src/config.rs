@@ -0,0 +1,216 @@
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+	pub key: String,
+	/// Where the offending value came from, e.g. `city.cfg:12` or `command line`.
+	pub origin: String,
+	pub message: String
+}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.key.is_empty() {
+			write!(f, "{}: {}", self.origin, self.message)
+		} else {
+			write!(f, "{}: `{}`: {}", self.origin, self.key, self.message)
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+	key: String,
+	value: String,
+	origin: String
+}
+
+/// Flat `key = value` options. Later entries override earlier ones, so
+/// command-line arguments parsed after a file take precedence.
+#[derive(Debug, Clone)]
+pub struct Config {
+	entries: Vec<Entry>
+}
+
+impl Config {
+	pub fn new() -> Config {
+		Config { entries: Vec::new() }
+	}
+
+	pub fn load(path: &Path) -> Result<Config, ConfigError> {
+		let name = format!("{}", path.display());
+		let mut source = String::new();
+		match File::open(path).and_then(|mut f| f.read_to_string(&mut source)) {
+			Ok(_) => {},
+			Err(e) => return Err(ConfigError {
+				key: String::new(),
+				origin: name,
+				message: format!("{}", e)
+			})
+		}
+
+		let mut config = Config::new();
+		config.parse(&source, &name)?;
+		Ok(config)
+	}
+
+	/// Parses lines of `key = value`. Blank lines and `#` comments are skipped.
+	pub fn parse(&mut self, source: &str, name: &str) -> Result<(), ConfigError> {
+		for (i, line) in source.lines().enumerate() {
+			let line = match line.find('#') {
+				Some(c) => &line[..c],
+				None => line
+			}.trim();
+			if line.is_empty() { continue; }
+
+			let origin = format!("{}:{}", name, i + 1);
+			match line.find('=') {
+				Some(eq) => {
+					let key = line[..eq].trim();
+					let value = line[eq + 1..].trim();
+					if key.is_empty() {
+						return Err(ConfigError { key: String::new(), origin: origin, message: String::from("missing key before '='") });
+					}
+					self.set(key, value, &origin);
+				},
+				None => {
+					return Err(ConfigError { key: String::from(line), origin: origin, message: String::from("expected `key = value`") });
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Parses `--key=value` and `--key value` arguments. A `--key` followed by
+	/// another option or nothing at all is treated as `--key=true`.
+	pub fn parse_args<I: Iterator<Item = String>>(&mut self, args: I) -> Result<(), ConfigError> {
+		let mut args = args.peekable();
+		while let Some(arg) = args.next() {
+			if !arg.starts_with("--") || arg.len() == 2 {
+				return Err(ConfigError {
+					key: arg,
+					origin: String::from("command line"),
+					message: String::from("expected `--key=value` or `--key value`")
+				});
+			}
+
+			let arg = &arg[2..];
+			match arg.find('=') {
+				Some(eq) => self.set(&arg[..eq], &arg[eq + 1..], "command line"),
+				None => {
+					let has_value = match args.peek() {
+						Some(next) => !next.starts_with("--"),
+						None => false
+					};
+					let value = if has_value { args.next().unwrap() } else { String::from("true") };
+					self.set(arg, &value, "command line");
+				}
+			}
+		}
+		Ok(())
+	}
+
+	pub fn set(&mut self, key: &str, value: &str, origin: &str) {
+		self.entries.push(Entry {
+			key: key.replace('-', "_"),
+			value: String::from(value),
+			origin: String::from(origin)
+		});
+	}
+
+	/// Appends the entries of `other`, which then take precedence.
+	pub fn merge(&mut self, other: &Config) {
+		self.entries.extend(other.entries.iter().cloned());
+	}
+
+	fn entry(&self, key: &str) -> Option<&Entry> {
+		self.entries.iter().rev().find(|e| e.key == key)
+	}
+
+	pub fn has(&self, key: &str) -> bool {
+		self.entry(key).is_some()
+	}
+
+	pub fn get_str(&self, key: &str) -> Option<&str> {
+		self.entry(key).map(|e| e.value.as_str())
+	}
+
+	pub fn get_opt<T: FromStr>(&self, key: &str) -> Result<Option<T>, ConfigError> {
+		match self.entry(key) {
+			Some(e) => match e.value.parse::<T>() {
+				Ok(v) => Ok(Some(v)),
+				Err(_) => Err(self.error(key, &format!("invalid value '{}'", e.value)))
+			},
+			None => Ok(None)
+		}
+	}
+
+	pub fn get<T: FromStr>(&self, key: &str, default: T) -> Result<T, ConfigError> {
+		self.get_opt(key).map(|v| v.unwrap_or(default))
+	}
+
+	/// Parses a `WIDTHxHEIGHT` pair such as `1280x720`.
+	pub fn get_size(&self, key: &str) -> Result<Option<(u32, u32)>, ConfigError> {
+		let value = match self.get_str(key) {
+			Some(v) => v,
+			None => return Ok(None)
+		};
+
+		let mut parts = value.split('x').map(|p| p.trim().parse::<u32>());
+		match (parts.next(), parts.next(), parts.next()) {
+			(Some(Ok(w)), Some(Ok(h)), None) => Ok(Some((w, h))),
+			_ => Err(self.error(key, &format!("expected WIDTHxHEIGHT, got '{}'", value)))
+		}
+	}
+
+	/// Every key that has been set, without duplicates, in first-seen order.
+	pub fn keys(&self) -> Vec<String> {
+		let mut keys: Vec<String> = Vec::new();
+		for e in self.entries.iter() {
+			if !keys.contains(&e.key) {
+				keys.push(e.key.clone());
+			}
+		}
+		keys
+	}
+
+	/// Serializes the current value of every key except those in `skip`, in a
+	/// form `parse` reads back.
+	pub fn write(&self, skip: &[&str]) -> String {
+		let mut out = String::new();
+		for key in self.keys() {
+			if skip.contains(&key.as_str()) { continue; }
+			out.push_str(&format!("{} = {}\n", key, self.get_str(&key).unwrap()));
+		}
+		out
+	}
+
+	/// Fails on the first key that isn't in `known`, to catch typos. Known
+	/// keys ending in `*` match any key with that prefix.
+	pub fn check_keys(&self, known: &[&str]) -> Result<(), ConfigError> {
+		let is_known = |key: &str| known.iter().any(|k| {
+			if k.ends_with('*') { key.starts_with(&k[..k.len() - 1]) } else { key == *k }
+		});
+		match self.entries.iter().find(|e| !is_known(&e.key)) {
+			Some(e) => Err(ConfigError {
+				key: e.key.clone(),
+				origin: e.origin.clone(),
+				message: String::from("unknown option")
+			}),
+			None => Ok(())
+		}
+	}
+
+	/// Builds an error pointing at wherever `key` was last set.
+	pub fn error(&self, key: &str, message: &str) -> ConfigError {
+		ConfigError {
+			key: String::from(key),
+			origin: self.entry(key).map(|e| e.origin.clone()).unwrap_or(String::from("defaults")),
+			message: String::from(message)
+		}
+	}
+}
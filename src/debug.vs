@@ -0,0 +1,14 @@
+#version 330
+
+layout (location = 0) in vec3 v_pos;
+layout (location = 1) in vec4 v_col;
+
+out vec4 color;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+void main() {
+	gl_Position = projection * view * vec4(v_pos, 1.0);
+	color = v_col;
+}
\ No newline at end of file
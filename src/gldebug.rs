@@ -0,0 +1,146 @@
+extern crate gl;
+extern crate log;
+
+use gl::types::*;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::ptr;
+
+/// Ordered from least to most severe.
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum Severity {
+	Notification,
+	Low,
+	Medium,
+	High
+}
+
+impl Severity {
+	fn from_gl(severity: GLenum) -> Severity {
+		match severity {
+			gl::DEBUG_SEVERITY_HIGH => Severity::High,
+			gl::DEBUG_SEVERITY_MEDIUM => Severity::Medium,
+			gl::DEBUG_SEVERITY_LOW => Severity::Low,
+			_ => Severity::Notification
+		}
+	}
+
+	fn to_gl(&self) -> GLenum {
+		match *self {
+			Severity::High => gl::DEBUG_SEVERITY_HIGH,
+			Severity::Medium => gl::DEBUG_SEVERITY_MEDIUM,
+			Severity::Low => gl::DEBUG_SEVERITY_LOW,
+			Severity::Notification => gl::DEBUG_SEVERITY_NOTIFICATION
+		}
+	}
+}
+
+/// Which debug messages reach the log. `sources` and `types` take
+/// `gl::DEBUG_SOURCE_*` and `gl::DEBUG_TYPE_*` values to silence.
+#[derive(Debug, Clone)]
+pub struct DebugFilter {
+	pub min_severity: Severity,
+	pub ignore_sources: Vec<GLenum>,
+	pub ignore_types: Vec<GLenum>,
+	/// Message ids to drop, e.g. chatty driver notices.
+	pub ignore_ids: Vec<GLuint>,
+	/// Deliver messages on the calling thread, inside the offending GL call.
+	pub synchronous: bool
+}
+
+impl Default for DebugFilter {
+	fn default() -> DebugFilter {
+		DebugFilter {
+			min_severity: Severity::Low,
+			ignore_sources: Vec::new(),
+			ignore_types: Vec::new(),
+			ignore_ids: Vec::new(),
+			synchronous: cfg!(debug_assertions)
+		}
+	}
+}
+
+fn source_name(source: GLenum) -> &'static str {
+	match source {
+		gl::DEBUG_SOURCE_API => "API",
+		gl::DEBUG_SOURCE_WINDOW_SYSTEM => "Window System",
+		gl::DEBUG_SOURCE_SHADER_COMPILER => "Shader Compiler",
+		gl::DEBUG_SOURCE_THIRD_PARTY => "Third Party",
+		gl::DEBUG_SOURCE_APPLICATION => "Application",
+		_ => "Other"
+	}
+}
+
+fn type_name(ty: GLenum) -> &'static str {
+	match ty {
+		gl::DEBUG_TYPE_ERROR => "Error",
+		gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "Deprecated",
+		gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "Undefined Behavior",
+		gl::DEBUG_TYPE_PORTABILITY => "Portability",
+		gl::DEBUG_TYPE_PERFORMANCE => "Performance",
+		gl::DEBUG_TYPE_MARKER => "Marker",
+		gl::DEBUG_TYPE_PUSH_GROUP => "Push Group",
+		gl::DEBUG_TYPE_POP_GROUP => "Pop Group",
+		_ => "Other"
+	}
+}
+
+extern "system" fn callback(
+	source: GLenum, ty: GLenum, id: GLuint, severity: GLenum,
+	_length: GLsizei, message: *const GLchar, _user: *mut c_void
+) {
+	let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+	let text = format!("[{} {} #{}] {}", source_name(source), type_name(ty), id, message);
+	match Severity::from_gl(severity) {
+		Severity::High => error!(target: "gl", "{}", text),
+		Severity::Medium => warn!(target: "gl", "{}", text),
+		Severity::Low => info!(target: "gl", "{}", text),
+		Severity::Notification => debug!(target: "gl", "{}", text)
+	}
+}
+
+/// Routes `KHR_debug` messages into the log. Needs a debug context; returns
+/// false when the driver doesn't expose debug output.
+pub fn install(filter: &DebugFilter) -> bool {
+	if !gl::DebugMessageCallback::is_loaded() || !gl::DebugMessageControl::is_loaded() {
+		return false;
+	}
+
+	GL!(Enable(gl::DEBUG_OUTPUT));
+	if filter.synchronous {
+		GL!(Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS));
+	}
+	GL!(DebugMessageCallback(callback, ptr::null()));
+
+	GL!(DebugMessageControl(gl::DONT_CARE, gl::DONT_CARE, gl::DONT_CARE, 0, ptr::null(), gl::TRUE));
+	for &s in [Severity::Notification, Severity::Low, Severity::Medium].iter() {
+		if s < filter.min_severity {
+			GL!(DebugMessageControl(gl::DONT_CARE, gl::DONT_CARE, s.to_gl(), 0, ptr::null(), gl::FALSE));
+		}
+	}
+	for &source in filter.ignore_sources.iter() {
+		GL!(DebugMessageControl(source, gl::DONT_CARE, gl::DONT_CARE, 0, ptr::null(), gl::FALSE));
+	}
+	for &ty in filter.ignore_types.iter() {
+		GL!(DebugMessageControl(gl::DONT_CARE, ty, gl::DONT_CARE, 0, ptr::null(), gl::FALSE));
+	}
+	if !filter.ignore_ids.is_empty() {
+		// Ids are only meaningful per source and type, so silence them everywhere
+		for &source in [
+			gl::DEBUG_SOURCE_API, gl::DEBUG_SOURCE_WINDOW_SYSTEM, gl::DEBUG_SOURCE_SHADER_COMPILER,
+			gl::DEBUG_SOURCE_THIRD_PARTY, gl::DEBUG_SOURCE_APPLICATION, gl::DEBUG_SOURCE_OTHER
+		].iter() {
+			for &ty in [
+				gl::DEBUG_TYPE_ERROR, gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR, gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR,
+				gl::DEBUG_TYPE_PORTABILITY, gl::DEBUG_TYPE_PERFORMANCE, gl::DEBUG_TYPE_OTHER,
+				gl::DEBUG_TYPE_MARKER, gl::DEBUG_TYPE_PUSH_GROUP, gl::DEBUG_TYPE_POP_GROUP
+			].iter() {
+				GL!(DebugMessageControl(
+					source, ty, gl::DONT_CARE,
+					filter.ignore_ids.len() as GLsizei, filter.ignore_ids.as_ptr(), gl::FALSE
+				));
+			}
+		}
+	}
+	true
+}
@@ -0,0 +1,173 @@
+extern crate sdl2;
+
+use std::fmt;
+use std::str::FromStr;
+use sdl2::keyboard::{ self, Keycode, Mod };
+use sdl2::mouse::MouseButton;
+
+/// Modifier keys, with left and right variants folded together.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Modifiers {
+	pub ctrl: bool,
+	pub shift: bool,
+	pub alt: bool
+}
+
+impl Modifiers {
+	pub fn none() -> Modifiers {
+		Modifiers::default()
+	}
+
+	pub fn from_sdl(m: Mod) -> Modifiers {
+		Modifiers {
+			ctrl: m.intersects(keyboard::LCTRLMOD | keyboard::RCTRLMOD),
+			shift: m.intersects(keyboard::LSHIFTMOD | keyboard::RSHIFTMOD),
+			alt: m.intersects(keyboard::LALTMOD | keyboard::RALTMOD)
+		}
+	}
+
+	/// True if every modifier set in `self` is also held in `held`.
+	pub fn satisfied_by(&self, held: Modifiers) -> bool {
+		(!self.ctrl || held.ctrl) && (!self.shift || held.shift) && (!self.alt || held.alt)
+	}
+
+	fn count(&self) -> u32 {
+		self.ctrl as u32 + self.shift as u32 + self.alt as u32
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Trigger {
+	Key(Keycode),
+	Mouse(MouseButton)
+}
+
+/// A key or mouse button plus the modifiers that must be held with it,
+/// written as e.g. `Return`, `MouseMiddle` or `Alt+MouseLeft`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Binding {
+	pub trigger: Trigger,
+	pub mods: Modifiers
+}
+
+impl Binding {
+	pub fn key(key: Keycode) -> Binding {
+		Binding { trigger: Trigger::Key(key), mods: Modifiers::none() }
+	}
+
+	pub fn mouse(button: MouseButton) -> Binding {
+		Binding { trigger: Trigger::Mouse(button), mods: Modifiers::none() }
+	}
+
+	pub fn with(mut self, mods: Modifiers) -> Binding {
+		self.mods = mods;
+		self
+	}
+}
+
+pub fn mouse_button_name(button: MouseButton) -> &'static str {
+	match button {
+		MouseButton::Left => "MouseLeft",
+		MouseButton::Middle => "MouseMiddle",
+		MouseButton::Right => "MouseRight",
+		MouseButton::X1 => "MouseX1",
+		MouseButton::X2 => "MouseX2",
+		MouseButton::Unknown => "MouseUnknown"
+	}
+}
+
+pub fn mouse_button_from_name(name: &str) -> Option<MouseButton> {
+	match name.to_lowercase().as_str() {
+		"mouseleft" => Some(MouseButton::Left),
+		"mousemiddle" => Some(MouseButton::Middle),
+		"mouseright" => Some(MouseButton::Right),
+		"mousex1" => Some(MouseButton::X1),
+		"mousex2" => Some(MouseButton::X2),
+		_ => None
+	}
+}
+
+impl fmt::Display for Binding {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.mods.ctrl { write!(f, "Ctrl+")?; }
+		if self.mods.shift { write!(f, "Shift+")?; }
+		if self.mods.alt { write!(f, "Alt+")?; }
+		match self.trigger {
+			Trigger::Key(k) => write!(f, "{}", k.name()),
+			Trigger::Mouse(b) => write!(f, "{}", mouse_button_name(b))
+		}
+	}
+}
+
+impl FromStr for Binding {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Binding, String> {
+		let parts: Vec<&str> = s.split('+').map(|p| p.trim()).collect();
+		let (name, mods) = parts.split_last().unwrap();
+
+		let mut m = Modifiers::none();
+		for &p in mods {
+			match p.to_lowercase().as_str() {
+				"ctrl" | "control" => m.ctrl = true,
+				"shift" => m.shift = true,
+				"alt" => m.alt = true,
+				_ => return Err(format!("unknown modifier '{}'", p))
+			}
+		}
+
+		let trigger = match mouse_button_from_name(name) {
+			Some(b) => Trigger::Mouse(b),
+			None => match Keycode::from_name(name) {
+				Some(k) => Trigger::Key(k),
+				None => return Err(format!("unknown key '{}'", name))
+			}
+		};
+
+		Ok(Binding { trigger: trigger, mods: m })
+	}
+}
+
+/// Maps keys and mouse buttons to application-defined actions.
+pub struct InputMap<A: Copy + PartialEq> {
+	bindings: Vec<(A, Binding)>
+}
+
+impl<A: Copy + PartialEq> InputMap<A> {
+	pub fn new() -> InputMap<A> {
+		InputMap { bindings: Vec::new() }
+	}
+
+	pub fn bind(&mut self, action: A, binding: Binding) {
+		self.bindings.push((action, binding));
+	}
+
+	/// Removes every binding of `action`, e.g. before loading user bindings.
+	pub fn unbind(&mut self, action: A) {
+		self.bindings.retain(|&(a, _)| a != action);
+	}
+
+	pub fn bindings(&self, action: A) -> Vec<Binding> {
+		self.bindings.iter().filter(|&&(a, _)| a == action).map(|&(_, b)| b).collect()
+	}
+
+	/// Finds the action for `trigger`. When several bindings match, the one
+	/// requiring the most modifiers wins, so `Alt+MouseLeft` beats `MouseLeft`.
+	pub fn action(&self, trigger: Trigger, held: Modifiers) -> Option<A> {
+		self.bindings.iter()
+			.filter(|&&(_, b)| b.trigger == trigger && b.mods.satisfied_by(held))
+			.fold(None, |best: Option<(A, u32)>, &(a, b)| match best {
+				Some((_, n)) if n >= b.mods.count() => best,
+				_ => Some((a, b.mods.count()))
+			})
+			.map(|(a, _)| a)
+	}
+
+	pub fn key_action(&self, key: Keycode, held: Modifiers) -> Option<A> {
+		self.action(Trigger::Key(key), held)
+	}
+
+	pub fn mouse_action(&self, button: MouseButton, held: Modifiers) -> Option<A> {
+		self.action(Trigger::Mouse(button), held)
+	}
+}
@@ -0,0 +1,22 @@
+#![feature(slice_patterns)]
+
+extern crate sdl2;
+extern crate gl;
+extern crate num;
+#[macro_use]
+extern crate log;
+
+pub mod vecmath;
+pub mod renderer;
+pub mod logger;
+pub mod gldebug;
+pub mod primitives;
+pub mod ui;
+pub mod tween;
+pub mod scene;
+pub mod spatial;
+pub mod camera;
+pub mod input;
+pub mod app;
+pub mod replay;
+pub mod config;
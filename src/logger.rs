@@ -0,0 +1,29 @@
+extern crate log;
+
+use self::log::{ Log, LogLevelFilter, LogMetadata, LogRecord };
+
+/// Minimal logger printing `[LEVEL target] message` to stdout.
+struct StdoutLogger {
+	level: LogLevelFilter
+}
+
+impl Log for StdoutLogger {
+	fn enabled(&self, metadata: &LogMetadata) -> bool {
+		metadata.level() <= self.level
+	}
+
+	fn log(&self, record: &LogRecord) {
+		if self.enabled(record.metadata()) {
+			println!("[{} {}] {}", record.level(), record.target(), record.args());
+		}
+	}
+}
+
+/// Installs the stdout logger. Does nothing if the application already set
+/// up its own logger.
+pub fn init(level: LogLevelFilter) {
+	let _ = log::set_logger(|max| {
+		max.set(level);
+		Box::new(StdoutLogger { level: level })
+	});
+}
@@ -0,0 +1,30 @@
+#version 330
+out vec4 fragColor;
+
+in vec2 uv;
+
+uniform sampler2D texture0;
+uniform vec2 texel;
+uniform float threshold = 1.0;
+uniform float intensity = 0.6;
+uniform float radius = 4.0;
+
+vec3 bright(vec2 p) {
+	vec3 c = texture(texture0, p).rgb;
+	return max(c - vec3(threshold), vec3(0.0));
+}
+
+void main() {
+	vec3 col = texture(texture0, uv).rgb;
+
+	// Two rings of taps around the pixel, weighted towards the center
+	vec3 glow = bright(uv) * 0.2;
+	for (int i = 0; i < 8; i++) {
+		float a = float(i) * 0.785398;
+		vec2 d = vec2(cos(a), sin(a)) * texel * radius;
+		glow += bright(uv + d) * 0.06;
+		glow += bright(uv + d * 2.0) * 0.04;
+	}
+
+	fragColor = vec4(col + glow * intensity, 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,10 @@
+#version 330
+out vec4 fragColor;
+
+in vec2 uv;
+
+uniform sampler2D texture0;
+
+void main() {
+	fragColor = vec4(texture(texture0, uv).rgb, 1.0);
+}
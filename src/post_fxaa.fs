@@ -0,0 +1,44 @@
+#version 330
+out vec4 fragColor;
+
+in vec2 uv;
+
+uniform sampler2D texture0;
+uniform vec2 texel;
+uniform float spanMax = 8.0;
+
+const float REDUCE_MIN = 1.0 / 128.0;
+const float REDUCE_MUL = 1.0 / 8.0;
+
+void main() {
+	const vec3 luma = vec3(0.299, 0.587, 0.114);
+	float lNW = dot(texture(texture0, uv + vec2(-1.0, -1.0) * texel).rgb, luma);
+	float lNE = dot(texture(texture0, uv + vec2( 1.0, -1.0) * texel).rgb, luma);
+	float lSW = dot(texture(texture0, uv + vec2(-1.0,  1.0) * texel).rgb, luma);
+	float lSE = dot(texture(texture0, uv + vec2( 1.0,  1.0) * texel).rgb, luma);
+	float lM = dot(texture(texture0, uv).rgb, luma);
+
+	float lMin = min(lM, min(min(lNW, lNE), min(lSW, lSE)));
+	float lMax = max(lM, max(max(lNW, lNE), max(lSW, lSE)));
+
+	vec2 dir = vec2(
+		-((lNW + lNE) - (lSW + lSE)),
+		((lNW + lSW) - (lNE + lSE))
+	);
+
+	float reduce = max((lNW + lNE + lSW + lSE) * (0.25 * REDUCE_MUL), REDUCE_MIN);
+	float rcpMin = 1.0 / (min(abs(dir.x), abs(dir.y)) + reduce);
+	dir = clamp(dir * rcpMin, vec2(-spanMax), vec2(spanMax)) * texel;
+
+	vec3 a = 0.5 * (
+		texture(texture0, uv + dir * (1.0 / 3.0 - 0.5)).rgb +
+		texture(texture0, uv + dir * (2.0 / 3.0 - 0.5)).rgb
+	);
+	vec3 b = a * 0.5 + 0.25 * (
+		texture(texture0, uv + dir * -0.5).rgb +
+		texture(texture0, uv + dir * 0.5).rgb
+	);
+
+	float lB = dot(b, luma);
+	fragColor = vec4((lB < lMin || lB > lMax) ? a : b, 1.0);
+}
\ No newline at end of file
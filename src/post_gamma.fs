@@ -0,0 +1,12 @@
+#version 330
+out vec4 fragColor;
+
+in vec2 uv;
+
+uniform sampler2D texture0;
+uniform float gamma = 2.2;
+
+void main() {
+	vec3 col = texture(texture0, uv).rgb;
+	fragColor = vec4(pow(col, vec3(1.0 / gamma)), 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+#version 330
+out vec4 fragColor;
+
+in vec2 uv;
+
+uniform sampler2D texture0;
+uniform sampler2D lut;
+uniform float lutSize = 16.0;
+uniform float intensity = 1.0;
+
+// The LUT is laid out as a horizontal strip of lutSize slices along blue
+vec3 lookup(vec3 c) {
+	float b = c.b * (lutSize - 1.0);
+	float s0 = floor(b);
+	float s1 = min(s0 + 1.0, lutSize - 1.0);
+
+	vec2 p = vec2(
+		(c.r * (lutSize - 1.0) + 0.5) / (lutSize * lutSize),
+		(c.g * (lutSize - 1.0) + 0.5) / lutSize
+	);
+	vec3 a = texture(lut, p + vec2(s0 / lutSize, 0.0)).rgb;
+	vec3 d = texture(lut, p + vec2(s1 / lutSize, 0.0)).rgb;
+	return mix(a, d, b - s0);
+}
+
+void main() {
+	vec3 col = clamp(texture(texture0, uv).rgb, 0.0, 1.0);
+	fragColor = vec4(mix(col, lookup(col), intensity), 1.0);
+}
\ No newline at end of file
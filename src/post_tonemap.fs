@@ -0,0 +1,22 @@
+#version 330
+out vec4 fragColor;
+
+in vec2 uv;
+
+uniform sampler2D texture0;
+uniform float exposure = 1.0;
+
+// ACES filmic curve fit by Krzysztof Narkowicz
+vec3 aces(vec3 x) {
+	const float a = 2.51;
+	const float b = 0.03;
+	const float c = 2.43;
+	const float d = 0.59;
+	const float e = 0.14;
+	return clamp((x * (a * x + b)) / (x * (c * x + d) + e), 0.0, 1.0);
+}
+
+void main() {
+	vec3 col = texture(texture0, uv).rgb * exposure;
+	fragColor = vec4(aces(col), 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,16 @@
+#version 330
+out vec4 fragColor;
+
+in vec2 uv;
+
+uniform sampler2D texture0;
+uniform float intensity = 0.4;
+uniform float radius = 0.75;
+uniform float softness = 0.45;
+
+void main() {
+	vec3 col = texture(texture0, uv).rgb;
+	float d = length(uv - vec2(0.5));
+	float v = smoothstep(radius, radius - softness, d);
+	fragColor = vec4(col * mix(1.0, v, intensity), 1.0);
+}
\ No newline at end of file
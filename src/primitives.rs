@@ -18,5 +18,25 @@ pub fn make_plane() -> Model {
 		VertexAttribute::new(2, false)
 	]);
 
+	Model::from(&verts, &inds, fmt)
+}
+
+pub fn make_quad() -> Model {
+	let verts = [
+		-1.0, -1.0, 0.0, 0.0,
+		 1.0, -1.0, 1.0, 0.0,
+		 1.0,  1.0, 1.0, 1.0,
+		-1.0,  1.0, 0.0, 1.0,
+	];
+
+	let inds = [
+		0, 1, 2, 2, 3, 0
+	];
+
+	let fmt = VertexFormat::new(&[
+		VertexAttribute::new(2, false),
+		VertexAttribute::new(2, false)
+	]);
+
 	Model::from(&verts, &inds, fmt)
 }
\ No newline at end of file
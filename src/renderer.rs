@@ -2,6 +2,7 @@
 
 extern crate tobj;
 extern crate stb_image;
+extern crate rusttype;
 extern crate gl;
 use gl::types::*;
 use std::mem;
@@ -9,28 +10,51 @@ use std::collections::HashMap;
 use std::ffi::{ CString, CStr };
 use std::ptr;
 use std::path::Path;
+use std::fs::File;
+use std::io::Read;
+use std::f32::consts::PI;
+use std::cmp::{ min, max };
 use self::stb_image::image;
+use self::rusttype::{ FontCollection, Scale, point };
 
 use vecmath::*;
+use primitives;
 
+/// Expands to the path of the enclosing function, e.g. `rustopengl::renderer::Shader::link`.
+#[macro_export]
+macro_rules! function_name {
+	() => {{
+		fn f() {}
+		fn type_name_of<T>(_: T) -> &'static str { ::std::any::type_name::<T>() }
+		let name = type_name_of(f);
+		&name[..name.len() - 3]
+	}};
+}
+
+/// Calls a `gl` function. Debug builds check `glGetError` after every call
+/// and panic with the call site; release builds leave error reporting to the
+/// debug-output callback.
 #[macro_export]
 macro_rules! GL {
 	($fun:ident ( $($arg:expr),*)) => {{
 		unsafe {
 			let result = ::gl::$fun( $($arg),* );
-			let err = ::gl::GetError();
-			if err != ::gl::NO_ERROR {
-				let err_str = match err {
-					::gl::INVALID_OPERATION => "Invalid Operation",
-					::gl::INVALID_ENUM => "Invalid Enum",
-					::gl::INVALID_VALUE => "Invalid Value",
-					::gl::OUT_OF_MEMORY => "Out Of Memory",
-					::gl::INVALID_FRAMEBUFFER_OPERATION => "Invalid Framebuffer Operation",
-					_ => "Unknown Error"
-				};
-				panic!("OpenGL Error ({}): {}\n\tFile:{}",
-					err, err_str, line!()
-				);
+			#[cfg(debug_assertions)]
+			{
+				let err = ::gl::GetError();
+				if err != ::gl::NO_ERROR {
+					let err_str = match err {
+						::gl::INVALID_OPERATION => "Invalid Operation",
+						::gl::INVALID_ENUM => "Invalid Enum",
+						::gl::INVALID_VALUE => "Invalid Value",
+						::gl::OUT_OF_MEMORY => "Out Of Memory",
+						::gl::INVALID_FRAMEBUFFER_OPERATION => "Invalid Framebuffer Operation",
+						_ => "Unknown Error"
+					};
+					panic!("OpenGL Error ({}): {} in gl{}\n\tFile: {}:{}\n\tFunction: {}",
+						err, err_str, stringify!($fun), file!(), line!(), function_name!()
+					);
+				}
 			}
 			result
 		}
@@ -75,6 +99,18 @@ impl Setter<Vec4> for Uniform {
 	}
 }
 
+impl Setter<Mat2> for Uniform {
+	fn set(&self, val: Mat2) {
+		GL!(UniformMatrix2fv(self.loc, 1, gl::TRUE, val.as_ptr()))
+	}
+}
+
+impl Setter<Mat3> for Uniform {
+	fn set(&self, val: Mat3) {
+		GL!(UniformMatrix3fv(self.loc, 1, gl::TRUE, val.as_ptr()))
+	}
+}
+
 impl Setter<Mat4> for Uniform {
 	fn set(&self, val: Mat4) {
 		GL!(UniformMatrix4fv(self.loc, 1, gl::TRUE, val.as_ptr()))
@@ -144,6 +180,13 @@ impl Shader {
 		Some(Uniform { loc: loc })
 	}
 
+	/// Sets the `model` matrix and, if the shader declares it, the matching
+	/// `normalMatrix`.
+	pub fn set_model(&mut self, model: Mat4) {
+		if let Some(u) = self.get("model") { u.set(model); }
+		if let Some(u) = self.get("normalMatrix") { u.set(Mat3::normal_matrix(model)); }
+	}
+
 	pub fn bind(&self) {
 		GL!(UseProgram(self.program));
 	}
@@ -232,6 +275,59 @@ impl Texture {
 		Texture { id: id }
 	}
 
+	pub fn from_data(width: i32, height: i32, data: &[u8]) -> Texture {
+		assert!(data.len() >= (width * height * 4) as usize);
+
+		let mut id = 0;
+		GL!(GenTextures(1, &mut id));
+		GL!(BindTexture(gl::TEXTURE_2D, id));
+
+		GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_BASE_LEVEL, 0));
+		GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 0));
+
+		GL!(TexImage2D(
+			gl::TEXTURE_2D,
+			0,
+			gl::RGBA8 as _,
+			width, height,
+			0,
+			gl::RGBA,
+			gl::UNSIGNED_BYTE,
+			data.as_ptr() as *const _
+		));
+
+		GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32));
+		GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32));
+		GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32));
+		GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32));
+
+		Texture { id: id }
+	}
+
+	pub fn empty(width: i32, height: i32, internal: GLenum, format: GLenum, ty: GLenum) -> Texture {
+		let mut id = 0;
+		GL!(GenTextures(1, &mut id));
+		GL!(BindTexture(gl::TEXTURE_2D, id));
+
+		GL!(TexImage2D(
+			gl::TEXTURE_2D,
+			0,
+			internal as _,
+			width, height,
+			0,
+			format,
+			ty,
+			ptr::null()
+		));
+
+		GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32));
+		GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32));
+		GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32));
+		GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32));
+
+		Texture { id: id }
+	}
+
 	pub fn bind(&self, slot: u32) {
 		GL!(ActiveTexture(gl::TEXTURE0 + slot));
 		GL!(BindTexture(gl::TEXTURE_2D, self.id));
@@ -426,6 +522,11 @@ impl Model {
 		self.indices.extend(indices);
 	}
 
+	pub fn clear(&mut self) {
+		self.vertices.clear();
+		self.indices.clear();
+	}
+
 	pub fn flush(&mut self) {
 		GL!(BindBuffer(gl::ARRAY_BUFFER, self.vbo));
 		if self.vertices.len() > self.prevVBO as usize {
@@ -468,7 +569,7 @@ impl Model {
 		GL!(BindVertexArray(self.vao));
 		GL!(DrawElements(
 			prim,
-			self.prevIBO as i32,
+			self.indices.len() as i32,
 			gl::UNSIGNED_SHORT,
 			0 as *const _
 		));
@@ -483,3 +584,1038 @@ impl Model {
 		}
 	}
 }
+
+
+#[derive(Debug, Copy, Clone)]
+struct DebugLine {
+	a: Vec3,
+	b: Vec3,
+	color: Vec4,
+	time: f32
+}
+
+/// Immediate-mode line renderer for debugging aids.
+/// Every shape is broken down into lines which are batched into a single
+/// dynamic buffer and drawn once per frame. Shapes with a duration of zero
+/// live for exactly one frame.
+pub struct DebugDraw {
+	shader: Shader,
+	batch: Model,
+	lines: Vec<DebugLine>,
+	pub depth_test: bool
+}
+
+const DEBUG_MAX_LINES: usize = 16384;
+const DEBUG_CIRCLE_SEGMENTS: usize = 24;
+
+impl DebugDraw {
+	pub fn new() -> DebugDraw {
+		let shd = Shader::new();
+		shd.add_shader(include_str!("debug.vs"), gl::VERTEX_SHADER);
+		shd.add_shader(include_str!("debug.fs"), gl::FRAGMENT_SHADER);
+		shd.link();
+
+		let fmt = VertexFormat::new(&[
+			VertexAttribute::new(3, false),
+			VertexAttribute::new(4, false)
+		]);
+
+		DebugDraw {
+			shader: shd,
+			batch: Model::new(fmt),
+			lines: Vec::new(),
+			depth_test: false
+		}
+	}
+
+	pub fn line(&mut self, a: Vec3, b: Vec3, color: Vec4, duration: f32) {
+		self.lines.push(DebugLine { a: a, b: b, color: color, time: duration });
+	}
+
+	pub fn wire_box(&mut self, min: Vec3, max: Vec3, color: Vec4, duration: f32) {
+		self.oriented_box(Mat4::identity(), min, max, color, duration);
+	}
+
+	/// Draws the `min`..`max` box transformed by `transform`.
+	pub fn oriented_box(&mut self, transform: Mat4, min: Vec3, max: Vec3, color: Vec4, duration: f32) {
+		let c = [
+			transform * Vec3::new(min.x, min.y, min.z),
+			transform * Vec3::new(max.x, min.y, min.z),
+			transform * Vec3::new(max.x, min.y, max.z),
+			transform * Vec3::new(min.x, min.y, max.z),
+			transform * Vec3::new(min.x, max.y, min.z),
+			transform * Vec3::new(max.x, max.y, min.z),
+			transform * Vec3::new(max.x, max.y, max.z),
+			transform * Vec3::new(min.x, max.y, max.z)
+		];
+		for i in 0..4 {
+			let j = (i + 1) % 4;
+			self.line(c[i], c[j], color, duration);
+			self.line(c[i + 4], c[j + 4], color, duration);
+			self.line(c[i], c[i + 4], color, duration);
+		}
+	}
+
+	/// Draws a circle lying on the XZ (ground) plane.
+	pub fn circle(&mut self, center: Vec3, radius: f32, color: Vec4, duration: f32) {
+		let step = (2.0 * PI) / DEBUG_CIRCLE_SEGMENTS as f32;
+		let point = |i: usize| {
+			let (s, c) = (i as f32 * step).sin_cos();
+			center + Vec3::new(c * radius, 0.0, s * radius)
+		};
+		for i in 0..DEBUG_CIRCLE_SEGMENTS {
+			let (a, b) = (point(i), point(i + 1));
+			self.line(a, b, color, duration);
+		}
+	}
+
+	pub fn arrow(&mut self, from: Vec3, to: Vec3, color: Vec4, duration: f32) {
+		self.line(from, to, color, duration);
+
+		let v = to - from;
+		let len = v.length();
+		if len <= 0.0 { return; }
+
+		let dir = v * (1.0 / len);
+		let mut side = dir.cross(Vec3::new(0.0, 1.0, 0.0));
+		if side.length() <= 0.001 {
+			side = dir.cross(Vec3::new(1.0, 0.0, 0.0));
+		}
+		let side = side.normalized();
+
+		let head = len * 0.25;
+		let back = to - dir * head;
+		self.line(to, back + side * (head * 0.5), color, duration);
+		self.line(to, back - side * (head * 0.5), color, duration);
+	}
+
+	/// Ages persistent shapes. Call once per simulation step.
+	pub fn update(&mut self, dt: f32) {
+		for line in self.lines.iter_mut() {
+			line.time -= dt;
+		}
+	}
+
+	pub fn render(&mut self, proj: Mat4, view: Mat4) {
+		if self.lines.is_empty() { return; }
+
+		if !self.depth_test { GL!(Disable(gl::DEPTH_TEST)); }
+
+		self.shader.bind();
+		self.shader.get("projection").unwrap().set(proj);
+		self.shader.get("view").unwrap().set(view);
+
+		for chunk in self.lines.chunks(DEBUG_MAX_LINES) {
+			self.batch.clear();
+			for (i, line) in chunk.iter().enumerate() {
+				let c = line.color;
+				let i = (i * 2) as u16;
+				self.batch.add_data(&[
+					line.a.x, line.a.y, line.a.z, c.x, c.y, c.z, c.w,
+					line.b.x, line.b.y, line.b.z, c.x, c.y, c.z, c.w
+				], &[i, i + 1]);
+			}
+			self.batch.flush();
+			self.batch.draw(gl::LINES);
+		}
+
+		self.shader.unbind();
+
+		if !self.depth_test { GL!(Enable(gl::DEPTH_TEST)); }
+
+		self.lines.retain(|l| l.time > 0.0);
+	}
+
+	pub fn free(&mut self) {
+		self.batch.free();
+	}
+}
+
+
+#[derive(Debug, Copy, Clone)]
+struct Glyph {
+	uv: Vec4,
+	offset: Vec2,
+	size: Vec2,
+	advance: f32
+}
+
+/// A positioned glyph quad, in pixels, relative to the top-left of the text.
+#[derive(Debug, Copy, Clone)]
+pub struct GlyphQuad {
+	pub pos: Vec2,
+	pub size: Vec2,
+	pub uv: Vec4
+}
+
+/// A TrueType font rasterized into a glyph atlas at a fixed pixel size.
+/// Only the printable ASCII range is baked.
+pub struct Font {
+	atlas: Texture,
+	glyphs: HashMap<char, Glyph>,
+	kerning: HashMap<(char, char), f32>,
+	ascent: f32,
+	line_height: f32
+}
+
+const FONT_FIRST_CHAR: u8 = 32;
+const FONT_LAST_CHAR: u8 = 126;
+const FONT_ATLAS_WIDTH: i32 = 512;
+
+impl Font {
+	pub fn new(path: &Path, size: f32) -> Font {
+		let mut bytes = Vec::new();
+		match File::open(path) {
+			Ok(mut f) => { f.read_to_end(&mut bytes).unwrap(); },
+			Err(e) => panic!("Font Error: {}", e)
+		}
+
+		let font = match FontCollection::from_bytes(bytes).into_font() {
+			Some(f) => f,
+			None => panic!("Font Error: Invalid font file.")
+		};
+
+		let scale = Scale::uniform(size);
+		let vm = font.v_metrics(scale);
+		let chars: Vec<char> = (FONT_FIRST_CHAR..FONT_LAST_CHAR + 1).map(|c| c as char).collect();
+
+		// Shelf-pack all glyphs into rows of the atlas
+		let mut glyphs = HashMap::new();
+		let mut placed = Vec::new();
+		let (mut x, mut y, mut row_h) = (1, 1, 0);
+		for &c in chars.iter() {
+			let g = font.glyph(c).unwrap().scaled(scale).positioned(point(0.0, 0.0));
+			let advance = g.unpositioned().h_metrics().advance_width;
+			let (gw, gh, offset) = match g.pixel_bounding_box() {
+				Some(bb) => (bb.width(), bb.height(), Vec2::new(bb.min.x as f32, bb.min.y as f32 + vm.ascent)),
+				None => (0, 0, Vec2::zero())
+			};
+
+			if x + gw + 1 > FONT_ATLAS_WIDTH {
+				x = 1;
+				y += row_h + 1;
+				row_h = 0;
+			}
+
+			placed.push((c, x, y, gw, gh));
+			glyphs.insert(c, Glyph {
+				uv: Vec4::new(x as f32, y as f32, (x + gw) as f32, (y + gh) as f32),
+				offset: offset,
+				size: Vec2::new(gw as f32, gh as f32),
+				advance: advance
+			});
+
+			x += gw + 1;
+			row_h = max(row_h, gh);
+		}
+
+		let atlas_h = ((y + row_h + 1) as u32).next_power_of_two() as i32;
+		let mut pixels = vec![0u8; (FONT_ATLAS_WIDTH * atlas_h * 4) as usize];
+		for &(c, gx, gy, _, _) in placed.iter() {
+			let g = font.glyph(c).unwrap().scaled(scale).positioned(point(0.0, 0.0));
+			g.draw(|px, py, v| {
+				let i = (((gy + py as i32) * FONT_ATLAS_WIDTH + gx + px as i32) * 4) as usize;
+				pixels[i] = 255;
+				pixels[i + 1] = 255;
+				pixels[i + 2] = 255;
+				pixels[i + 3] = (v * 255.0) as u8;
+			});
+		}
+
+		let (aw, ah) = (FONT_ATLAS_WIDTH as f32, atlas_h as f32);
+		for g in glyphs.values_mut() {
+			g.uv = Vec4::new(g.uv.x / aw, g.uv.y / ah, g.uv.z / aw, g.uv.w / ah);
+		}
+
+		let mut kerning = HashMap::new();
+		for &a in chars.iter() {
+			for &b in chars.iter() {
+				let k = font.pair_kerning(scale, a, b);
+				if k != 0.0 { kerning.insert((a, b), k); }
+			}
+		}
+
+		Font {
+			atlas: Texture::from_data(FONT_ATLAS_WIDTH, atlas_h, &pixels),
+			glyphs: glyphs,
+			kerning: kerning,
+			ascent: vm.ascent,
+			line_height: vm.ascent - vm.descent + vm.line_gap
+		}
+	}
+
+	pub fn line_height(&self) -> f32 { self.line_height }
+
+	pub fn ascent(&self) -> f32 { self.ascent }
+
+	/// Lays out `text` applying kerning and breaking lines at '\n'.
+	/// Characters outside the baked range are rendered as '?'.
+	pub fn layout(&self, text: &str) -> Vec<GlyphQuad> {
+		let mut quads = Vec::new();
+		let mut pen = Vec2::zero();
+		let mut prev: Option<char> = None;
+
+		for c in text.chars() {
+			if c == '\n' {
+				pen.x = 0.0;
+				pen.y += self.line_height;
+				prev = None;
+				continue;
+			}
+
+			let c = if self.glyphs.contains_key(&c) { c } else { '?' };
+			if let Some(p) = prev {
+				if let Some(k) = self.kerning.get(&(p, c)) {
+					pen.x += *k;
+				}
+			}
+
+			let g = self.glyphs[&c];
+			if g.size.x > 0.0 {
+				quads.push(GlyphQuad { pos: pen + g.offset, size: g.size, uv: g.uv });
+			}
+			pen.x += g.advance;
+			prev = Some(c);
+		}
+		quads
+	}
+
+	pub fn measure(&self, text: &str) -> Vec2 {
+		let mut size = Vec2::new(0.0, self.line_height);
+		let mut width = 0.0f32;
+		let mut prev: Option<char> = None;
+
+		for c in text.chars() {
+			if c == '\n' {
+				size.y += self.line_height;
+				width = 0.0;
+				prev = None;
+				continue;
+			}
+
+			let c = if self.glyphs.contains_key(&c) { c } else { '?' };
+			if let Some(p) = prev {
+				width += *self.kerning.get(&(p, c)).unwrap_or(&0.0);
+			}
+			width += self.glyphs[&c].advance;
+			size.x = size.x.max(width);
+			prev = Some(c);
+		}
+		size
+	}
+
+	pub fn bind(&self, slot: u32) {
+		self.atlas.bind(slot);
+	}
+
+	pub fn free(&mut self) {
+		self.atlas.free();
+	}
+}
+
+struct TextItem {
+	text: String,
+	pos: Vec3,
+	scale: f32,
+	color: Vec4,
+	world: bool
+}
+
+/// Batches strings drawn with a single font. Screen-space text is placed in
+/// pixels from the top-left corner, world-space text is drawn as billboards
+/// facing the camera.
+pub struct TextRenderer {
+	pub font: Font,
+	shader: Shader,
+	batch: Model,
+	items: Vec<TextItem>
+}
+
+/// Glyphs per draw call, so vertex indices fit in a `u16`.
+const TEXT_MAX_QUADS: usize = 16384;
+
+impl TextRenderer {
+	pub fn new(font: Font) -> TextRenderer {
+		let shd = Shader::new();
+		shd.add_shader(include_str!("text.vs"), gl::VERTEX_SHADER);
+		shd.add_shader(include_str!("text.fs"), gl::FRAGMENT_SHADER);
+		shd.link();
+
+		let fmt = VertexFormat::new(&[
+			VertexAttribute::new(3, false),
+			VertexAttribute::new(2, false),
+			VertexAttribute::new(4, false)
+		]);
+
+		TextRenderer {
+			font: font,
+			shader: shd,
+			batch: Model::new(fmt),
+			items: Vec::new()
+		}
+	}
+
+	pub fn text(&mut self, text: &str, pos: Vec2, color: Vec4) {
+		self.items.push(TextItem {
+			text: text.to_owned(),
+			pos: pos.extend(0.0),
+			scale: 1.0,
+			color: color,
+			world: false
+		});
+	}
+
+	/// Draws `text` centered above `pos`. `scale` is the world size of one pixel of the font.
+	pub fn billboard(&mut self, text: &str, pos: Vec3, scale: f32, color: Vec4) {
+		self.items.push(TextItem {
+			text: text.to_owned(),
+			pos: pos,
+			scale: scale,
+			color: color,
+			world: true
+		});
+	}
+
+	/// Appends glyph quads, drawing and emptying the batch first whenever it
+	/// is full. The shader's matrices must already be set.
+	fn push_quads<F: Fn(Vec2) -> Vec3>(batch: &mut Model, quads: &[GlyphQuad], color: Vec4, place: F) {
+		let c = color;
+		for q in quads.iter() {
+			if batch.vertices.len() / 9 >= TEXT_MAX_QUADS * 4 {
+				TextRenderer::draw_batch(batch);
+			}
+			let corners = [
+				(Vec2::new(q.pos.x, q.pos.y), Vec2::new(q.uv.x, q.uv.y)),
+				(Vec2::new(q.pos.x + q.size.x, q.pos.y), Vec2::new(q.uv.z, q.uv.y)),
+				(Vec2::new(q.pos.x + q.size.x, q.pos.y + q.size.y), Vec2::new(q.uv.z, q.uv.w)),
+				(Vec2::new(q.pos.x, q.pos.y + q.size.y), Vec2::new(q.uv.x, q.uv.w))
+			];
+
+			let base = (batch.vertices.len() / 9) as u16;
+			for &(p, uv) in corners.iter() {
+				let v = place(p);
+				batch.add_data(&[v.x, v.y, v.z, uv.x, uv.y, c.x, c.y, c.z, c.w], &[]);
+			}
+			batch.add_data(&[], &[base, base + 1, base + 2, base + 2, base + 3, base]);
+		}
+	}
+
+	fn draw_batch(batch: &mut Model) {
+		batch.flush();
+		batch.draw(gl::TRIANGLES);
+		batch.clear();
+	}
+
+	pub fn render(&mut self, proj: Mat4, view: Mat4, w: f32, h: f32) {
+		if self.items.is_empty() { return; }
+
+		let viewproj = proj * view;
+		let inv = viewproj.inverted();
+		let to_world = |ndc: Vec3| {
+			let p = inv * ndc.extend(1.0);
+			Vec3::new(p.x / p.w, p.y / p.w, p.z / p.w)
+		};
+
+		GL!(Disable(gl::DEPTH_TEST));
+		self.shader.bind();
+		self.shader.get("texture0").unwrap().set(0);
+		self.font.bind(0);
+
+		// World-space billboards
+		self.shader.get("projection").unwrap().set(proj);
+		self.shader.get("view").unwrap().set(view);
+		self.batch.clear();
+		for item in self.items.iter().filter(|i| i.world) {
+			let clip = viewproj * item.pos.extend(1.0);
+			let ndc = Vec3::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w);
+			let origin = to_world(ndc);
+			let right = (to_world(ndc + Vec3::new(1.0, 0.0, 0.0)) - origin).normalized();
+			let up = (to_world(ndc + Vec3::new(0.0, 1.0, 0.0)) - origin).normalized();
+
+			let size = self.font.measure(&item.text);
+			let (pos, s) = (item.pos, item.scale);
+			let quads = self.font.layout(&item.text);
+			TextRenderer::push_quads(&mut self.batch, &quads, item.color, |p: Vec2| {
+				pos + right * ((p.x - size.x * 0.5) * s) + up * ((size.y - p.y) * s)
+			});
+		}
+		TextRenderer::draw_batch(&mut self.batch);
+
+		// Screen-space text
+		self.shader.get("projection").unwrap().set(Mat4::ortho(0.0, w, h, 0.0, -1.0, 1.0));
+		self.shader.get("view").unwrap().set(Mat4::identity());
+		for item in self.items.iter().filter(|i| !i.world) {
+			let pos = item.pos;
+			let quads = self.font.layout(&item.text);
+			TextRenderer::push_quads(&mut self.batch, &quads, item.color, |p: Vec2| {
+				Vec3::new(pos.x + p.x, pos.y + p.y, 0.0)
+			});
+		}
+		TextRenderer::draw_batch(&mut self.batch);
+
+		self.shader.unbind();
+		GL!(Enable(gl::DEPTH_TEST));
+
+		self.items.clear();
+	}
+
+	pub fn free(&mut self) {
+		self.font.free();
+		self.batch.free();
+	}
+}
+
+
+/// An offscreen framebuffer with optional color and depth texture attachments.
+/// Multisampled targets use renderbuffers instead and must be resolved into a
+/// single-sampled target (or the screen) before their contents can be sampled.
+pub struct RenderTarget {
+	fbo: u32,
+	color: Option<Texture>,
+	depth: Option<Texture>,
+	renderbuffers: Vec<u32>,
+	width: i32,
+	height: i32,
+	samples: i32
+}
+
+impl RenderTarget {
+	pub fn new(width: i32, height: i32, color_format: Option<GLenum>, depth: bool) -> RenderTarget {
+		let mut fbo = 0;
+		GL!(GenFramebuffers(1, &mut fbo));
+		GL!(BindFramebuffer(gl::FRAMEBUFFER, fbo));
+
+		let color = match color_format {
+			Some(ifmt) => {
+				let ty = match ifmt {
+					gl::RGBA16F | gl::RGBA32F | gl::RGB16F | gl::RGB32F => gl::FLOAT,
+					_ => gl::UNSIGNED_BYTE
+				};
+				let tex = Texture::empty(width, height, ifmt, gl::RGBA, ty);
+				GL!(FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, tex.id, 0));
+				Some(tex)
+			},
+			None => {
+				GL!(DrawBuffer(gl::NONE));
+				GL!(ReadBuffer(gl::NONE));
+				None
+			}
+		};
+
+		let depth = if depth {
+			let tex = Texture::empty(width, height, gl::DEPTH_COMPONENT24, gl::DEPTH_COMPONENT, gl::FLOAT);
+			GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32));
+			GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32));
+			GL!(FramebufferTexture2D(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::TEXTURE_2D, tex.id, 0));
+			Some(tex)
+		} else {
+			None
+		};
+
+		RenderTarget::check_status();
+		GL!(BindFramebuffer(gl::FRAMEBUFFER, 0));
+
+		RenderTarget {
+			fbo: fbo,
+			color: color,
+			depth: depth,
+			renderbuffers: Vec::new(),
+			width: width,
+			height: height,
+			samples: 0
+		}
+	}
+
+	/// Creates a multisampled color + depth target. The sample count is clamped
+	/// to what the driver supports; with less than two samples this falls back
+	/// to a regular texture-backed target.
+	pub fn multisampled(width: i32, height: i32, color_format: GLenum, samples: i32) -> RenderTarget {
+		let max = RenderTarget::max_samples();
+		if samples > max {
+			warn!("MSAA x{} not supported, using x{}.", samples, max);
+		}
+		let samples = min(samples, max);
+		if samples < 2 {
+			return RenderTarget::new(width, height, Some(color_format), true);
+		}
+
+		let mut fbo = 0;
+		GL!(GenFramebuffers(1, &mut fbo));
+		GL!(BindFramebuffer(gl::FRAMEBUFFER, fbo));
+
+		let mut rbos = [0u32; 2];
+		GL!(GenRenderbuffers(2, rbos.as_mut_ptr()));
+
+		GL!(BindRenderbuffer(gl::RENDERBUFFER, rbos[0]));
+		GL!(RenderbufferStorageMultisample(gl::RENDERBUFFER, samples, color_format, width, height));
+		GL!(FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::RENDERBUFFER, rbos[0]));
+
+		GL!(BindRenderbuffer(gl::RENDERBUFFER, rbos[1]));
+		GL!(RenderbufferStorageMultisample(gl::RENDERBUFFER, samples, gl::DEPTH_COMPONENT24, width, height));
+		GL!(FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, rbos[1]));
+		GL!(BindRenderbuffer(gl::RENDERBUFFER, 0));
+
+		RenderTarget::check_status();
+		GL!(BindFramebuffer(gl::FRAMEBUFFER, 0));
+
+		RenderTarget {
+			fbo: fbo,
+			color: None,
+			depth: None,
+			renderbuffers: rbos.to_vec(),
+			width: width,
+			height: height,
+			samples: samples
+		}
+	}
+
+	pub fn max_samples() -> i32 {
+		let mut max = 0;
+		GL!(GetIntegerv(gl::MAX_SAMPLES, &mut max));
+		max
+	}
+
+	fn check_status() {
+		let status = GL!(CheckFramebufferStatus(gl::FRAMEBUFFER));
+		if status != gl::FRAMEBUFFER_COMPLETE {
+			panic!("Incomplete framebuffer: 0x{:X}", status);
+		}
+	}
+
+	pub fn width(&self) -> i32 { self.width }
+	pub fn height(&self) -> i32 { self.height }
+	pub fn samples(&self) -> i32 { self.samples }
+
+	pub fn color(&self) -> Option<&Texture> { self.color.as_ref() }
+	pub fn depth(&self) -> Option<&Texture> { self.depth.as_ref() }
+
+	fn has_depth(&self) -> bool {
+		self.depth.is_some() || self.samples > 1
+	}
+
+	/// Binds the target for drawing and sets the viewport to cover it.
+	pub fn bind(&self) {
+		GL!(BindFramebuffer(gl::FRAMEBUFFER, self.fbo));
+		GL!(Viewport(0, 0, self.width, self.height));
+	}
+
+	pub fn unbind(&self) {
+		GL!(BindFramebuffer(gl::FRAMEBUFFER, 0));
+	}
+
+	/// Resolves (or copies) this target into `dst`, including depth when both have it.
+	pub fn resolve(&self, dst: &RenderTarget) {
+		let mut mask = gl::COLOR_BUFFER_BIT;
+		if self.has_depth() && dst.has_depth() { mask |= gl::DEPTH_BUFFER_BIT; }
+		self.blit(dst.fbo, dst.width, dst.height, mask);
+	}
+
+	/// Resolves this target's color into the default framebuffer, which must
+	/// not be multisampled.
+	pub fn resolve_to_screen(&self, width: i32, height: i32) {
+		self.blit(0, width, height, gl::COLOR_BUFFER_BIT);
+	}
+
+	fn blit(&self, dst: u32, width: i32, height: i32, mask: GLenum) {
+		// Depth can only be blitted with nearest filtering, and resolving
+		// requires matching sizes anyway
+		let filter = if mask & gl::DEPTH_BUFFER_BIT != 0 || self.samples > 1 { gl::NEAREST } else { gl::LINEAR };
+		GL!(BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo));
+		GL!(BindFramebuffer(gl::DRAW_FRAMEBUFFER, dst));
+		GL!(BlitFramebuffer(
+			0, 0, self.width, self.height,
+			0, 0, width, height,
+			mask, filter
+		));
+		GL!(BindFramebuffer(gl::FRAMEBUFFER, 0));
+	}
+
+	pub fn free(&mut self) {
+		if let Some(ref mut t) = self.color { t.free(); }
+		if let Some(ref mut t) = self.depth { t.free(); }
+		if !self.renderbuffers.is_empty() {
+			GL!(DeleteRenderbuffers(self.renderbuffers.len() as i32, self.renderbuffers.as_ptr()));
+			self.renderbuffers.clear();
+		}
+		if self.fbo > 0 {
+			GL!(DeleteFramebuffers(1, &mut self.fbo));
+			self.fbo = 0;
+		}
+	}
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Light {
+	pub direction: Vec3,
+	pub color: Vec3,
+	pub ambient: Vec3
+}
+
+impl Light {
+	pub fn new(direction: Vec3, color: Vec3, ambient: Vec3) -> Light {
+		Light { direction: direction, color: color, ambient: ambient }
+	}
+
+	pub fn apply(&self, shader: &mut Shader) {
+		if let Some(u) = shader.get("lightDir") { u.set(self.direction.normalized()); }
+		if let Some(u) = shader.get("lightColor") { u.set(self.color); }
+		if let Some(u) = shader.get("ambient") { u.set(self.ambient); }
+	}
+}
+
+/// Depth-only render of the scene from a directional light.
+pub struct ShadowMap {
+	target: RenderTarget,
+	shader: Shader,
+	pub light_space: Mat4
+}
+
+impl ShadowMap {
+	pub fn new(size: i32) -> ShadowMap {
+		let target = RenderTarget::new(size, size, None, true);
+
+		// Anything outside the map must read as fully lit
+		target.depth().unwrap().bind(0);
+		let border = [1.0f32, 1.0, 1.0, 1.0];
+		GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_BORDER as i32));
+		GL!(TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_BORDER as i32));
+		GL!(TexParameterfv(gl::TEXTURE_2D, gl::TEXTURE_BORDER_COLOR, border.as_ptr()));
+
+		let shd = Shader::new();
+		shd.add_shader(include_str!("shadow.vs"), gl::VERTEX_SHADER);
+		shd.add_shader(include_str!("shadow.fs"), gl::FRAGMENT_SHADER);
+		shd.link();
+
+		ShadowMap {
+			target: target,
+			shader: shd,
+			light_space: Mat4::identity()
+		}
+	}
+
+	/// Fits the light's orthographic frustum around the part of the ground plane
+	/// visible through `viewproj`, clipped to the `min`..`max` box of the map.
+	pub fn fit(&mut self, light: &Light, viewproj: Mat4, min: Vec3, max: Vec3) {
+		let inv = viewproj.inverted();
+		let to_world = |x: f32, y: f32, z: f32| {
+			let p = inv * Vec4::new(x, y, z, 1.0);
+			Vec3::new(p.x / p.w, p.y / p.w, p.z / p.w)
+		};
+
+		// Intersect the corner rays of the view with the ground (y = 0)
+		let (mut lo, mut hi) = (max, min);
+		for &(x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].iter() {
+			let a = to_world(x, y, -1.0);
+			let b = to_world(x, y, 1.0);
+			let d = b.y - a.y;
+			let p = if d.abs() > 1e-6 { a + (b - a) * ((min.y - a.y) / d) } else { a };
+			lo = Vec3::new(lo.x.min(p.x), min.y, lo.z.min(p.z));
+			hi = Vec3::new(hi.x.max(p.x), max.y, hi.z.max(p.z));
+		}
+		lo = Vec3::new(lo.x.max(min.x), min.y, lo.z.max(min.z));
+		hi = Vec3::new(hi.x.min(max.x), max.y, hi.z.min(max.z));
+		if lo.x >= hi.x || lo.z >= hi.z {
+			lo = min;
+			hi = max;
+		}
+
+		let fwd = light.direction.normalized();
+		let up = if fwd.y.abs() > 0.99 { Vec3::new(0.0, 0.0, 1.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+		let view = Mat4::look_at(Vec3::zero(), fwd, up);
+		let (vmin, vmax) = view.transform_aabb(lo, hi);
+
+		let proj = Mat4::ortho(vmin.x, vmax.x, vmin.y, vmax.y, -vmax.z - 1.0, -vmin.z + 1.0);
+		self.light_space = proj * view;
+	}
+
+	/// Binds the shadow target and returns the depth shader, ready for drawing casters.
+	pub fn begin(&mut self) -> &mut Shader {
+		self.target.bind();
+		GL!(Clear(gl::DEPTH_BUFFER_BIT));
+		self.shader.bind();
+		self.shader.get("lightSpace").unwrap().set(self.light_space);
+		&mut self.shader
+	}
+
+	pub fn end(&self) {
+		self.shader.unbind();
+		self.target.unbind();
+	}
+
+	pub fn bind(&self, slot: u32) {
+		self.target.depth().unwrap().bind(slot);
+	}
+
+	pub fn free(&mut self) {
+		self.target.free();
+	}
+}
+
+
+/// A single full-screen post-processing effect.
+pub struct PostPass {
+	pub name: String,
+	pub enabled: bool,
+	shader: Shader,
+	params: Vec<(String, f32)>,
+	textures: Vec<(String, Texture)>
+}
+
+impl PostPass {
+	pub fn new(name: &str, fs: &str) -> PostPass {
+		let shd = Shader::new();
+		shd.add_shader(include_str!("post.vs"), gl::VERTEX_SHADER);
+		shd.add_shader(fs, gl::FRAGMENT_SHADER);
+		shd.link();
+
+		PostPass {
+			name: name.to_owned(),
+			enabled: true,
+			shader: shd,
+			params: Vec::new(),
+			textures: Vec::new()
+		}
+	}
+
+	pub fn bloom() -> PostPass {
+		let mut p = PostPass::new("Bloom", include_str!("post_bloom.fs"));
+		p.set("threshold", 1.0);
+		p.set("intensity", 0.6);
+		p.set("radius", 4.0);
+		p
+	}
+
+	pub fn tonemap() -> PostPass {
+		let mut p = PostPass::new("Tone Mapping", include_str!("post_tonemap.fs"));
+		p.set("exposure", 1.0);
+		p
+	}
+
+	/// Colour grading through a 3D LUT stored as a strip of `size` slices,
+	/// `size * size` pixels wide. Without a LUT an identity one is generated.
+	pub fn color_grade(lut: Option<Texture>, size: i32) -> PostPass {
+		let mut p = PostPass::new("Color Grading", include_str!("post_grade.fs"));
+		p.set("lutSize", size as f32);
+		p.set("intensity", 1.0);
+		p.set_texture("lut", match lut {
+			Some(t) => t,
+			None => PostPass::identity_lut(size)
+		});
+		p
+	}
+
+	pub fn fxaa() -> PostPass {
+		let mut p = PostPass::new("FXAA", include_str!("post_fxaa.fs"));
+		p.set("spanMax", 8.0);
+		p
+	}
+
+	pub fn vignette() -> PostPass {
+		let mut p = PostPass::new("Vignette", include_str!("post_vignette.fs"));
+		p.set("intensity", 0.4);
+		p.set("radius", 0.75);
+		p.set("softness", 0.45);
+		p
+	}
+
+	pub fn gamma() -> PostPass {
+		let mut p = PostPass::new("Gamma", include_str!("post_gamma.fs"));
+		p.set("gamma", 2.2);
+		p
+	}
+
+	fn identity_lut(size: i32) -> Texture {
+		let mut data = Vec::with_capacity((size * size * size * 4) as usize);
+		let max = (size - 1) as f32;
+		for g in 0..size {
+			for b in 0..size {
+				for r in 0..size {
+					data.push((r as f32 / max * 255.0) as u8);
+					data.push((g as f32 / max * 255.0) as u8);
+					data.push((b as f32 / max * 255.0) as u8);
+					data.push(255);
+				}
+			}
+		}
+		Texture::from_data(size * size, size, &data)
+	}
+
+	pub fn set(&mut self, name: &str, value: f32) {
+		match self.params.iter_mut().find(|p| p.0 == name) {
+			Some(p) => { p.1 = value; return; },
+			None => {}
+		}
+		self.params.push((name.to_owned(), value));
+	}
+
+	pub fn get(&self, name: &str) -> Option<f32> {
+		self.params.iter().find(|p| p.0 == name).map(|p| p.1)
+	}
+
+	pub fn set_texture(&mut self, name: &str, tex: Texture) {
+		match self.textures.iter().position(|t| t.0 == name) {
+			Some(i) => {
+				self.textures[i].1.free();
+				self.textures[i].1 = tex;
+			},
+			None => { self.textures.push((name.to_owned(), tex)); }
+		}
+	}
+
+	fn apply(&mut self, input: &Texture, width: i32, height: i32) {
+		self.shader.bind();
+		input.bind(0);
+		if let Some(u) = self.shader.get("texture0") { u.set(0); }
+		if let Some(u) = self.shader.get("texel") {
+			u.set(Vec2::new(1.0 / width as f32, 1.0 / height as f32));
+		}
+		for &(ref name, value) in self.params.iter() {
+			if let Some(u) = self.shader.get(name) { u.set(value); }
+		}
+		for (i, &(ref name, ref tex)) in self.textures.iter().enumerate() {
+			let slot = i as u32 + 1;
+			tex.bind(slot);
+			if let Some(u) = self.shader.get(name) { u.set(slot as i32); }
+		}
+	}
+
+	pub fn free(&mut self) {
+		for t in self.textures.iter_mut() {
+			t.1.free();
+		}
+	}
+}
+
+/// Renders the scene into an HDR target and runs it through a chain of
+/// full-screen passes, the last enabled one writing to the screen.
+pub struct PostStack {
+	pub passes: Vec<PostPass>,
+	scene: RenderTarget,
+	resolved: Option<RenderTarget>,
+	ping: [RenderTarget; 2],
+	/// Used instead of a blit when no pass is enabled, since the window may
+	/// be multisampled and blitting into it isn't allowed
+	copy: PostPass,
+	quad: Model,
+	width: i32,
+	height: i32,
+	samples: i32
+}
+
+const HDR_FORMAT: GLenum = gl::RGBA16F;
+
+impl PostStack {
+	pub fn new(width: i32, height: i32, samples: i32) -> PostStack {
+		let (scene, resolved) = PostStack::create_scene(width, height, samples);
+		PostStack {
+			passes: Vec::new(),
+			scene: scene,
+			resolved: resolved,
+			ping: [
+				RenderTarget::new(width, height, Some(HDR_FORMAT), false),
+				RenderTarget::new(width, height, Some(HDR_FORMAT), false)
+			],
+			copy: PostPass::new("Copy", include_str!("post_copy.fs")),
+			quad: primitives::make_quad(),
+			width: width,
+			height: height,
+			samples: samples
+		}
+	}
+
+	fn create_scene(width: i32, height: i32, samples: i32) -> (RenderTarget, Option<RenderTarget>) {
+		let scene = RenderTarget::multisampled(width, height, HDR_FORMAT, samples);
+		let resolved = if scene.samples() > 1 {
+			Some(RenderTarget::new(width, height, Some(HDR_FORMAT), true))
+		} else {
+			None
+		};
+		(scene, resolved)
+	}
+
+	pub fn width(&self) -> i32 { self.width }
+	pub fn height(&self) -> i32 { self.height }
+
+	pub fn add(&mut self, pass: PostPass) {
+		self.passes.push(pass);
+	}
+
+	pub fn pass_mut(&mut self, name: &str) -> Option<&mut PostPass> {
+		self.passes.iter_mut().find(|p| p.name == name)
+	}
+
+	pub fn resize(&mut self, width: i32, height: i32) {
+		if width == self.width && height == self.height { return; }
+
+		self.free_targets();
+		let (scene, resolved) = PostStack::create_scene(width, height, self.samples);
+		self.scene = scene;
+		self.resolved = resolved;
+		self.ping = [
+			RenderTarget::new(width, height, Some(HDR_FORMAT), false),
+			RenderTarget::new(width, height, Some(HDR_FORMAT), false)
+		];
+		self.width = width;
+		self.height = height;
+	}
+
+	/// Redirects drawing into the HDR scene target.
+	pub fn begin(&self) {
+		self.scene.bind();
+	}
+
+	/// Resolves the scene and runs the enabled passes, ending on the screen.
+	pub fn end(&mut self) {
+		let input = match self.resolved {
+			Some(ref r) => { self.scene.resolve(r); r },
+			None => &self.scene
+		};
+
+		let enabled: Vec<usize> = (0..self.passes.len()).filter(|&i| self.passes[i].enabled).collect();
+
+		GL!(Disable(gl::DEPTH_TEST));
+		GL!(Disable(gl::BLEND));
+
+		let mut src = input.color().unwrap();
+		if enabled.is_empty() {
+			input.unbind();
+			GL!(Viewport(0, 0, self.width, self.height));
+			self.copy.apply(src, self.width, self.height);
+			self.quad.draw(gl::TRIANGLES);
+		}
+		for (n, &i) in enabled.iter().enumerate() {
+			let last = n + 1 == enabled.len();
+			let dst = &self.ping[n % 2];
+			if last {
+				dst.unbind();
+				GL!(Viewport(0, 0, self.width, self.height));
+			} else {
+				dst.bind();
+			}
+
+			self.passes[i].apply(src, self.width, self.height);
+			self.quad.draw(gl::TRIANGLES);
+			src = dst.color().unwrap();
+		}
+
+		GL!(UseProgram(0));
+		GL!(Enable(gl::BLEND));
+		GL!(Enable(gl::DEPTH_TEST));
+	}
+
+	fn free_targets(&mut self) {
+		self.scene.free();
+		if let Some(ref mut r) = self.resolved { r.free(); }
+		self.ping[0].free();
+		self.ping[1].free();
+	}
+
+	pub fn free(&mut self) {
+		self.free_targets();
+		for p in self.passes.iter_mut() {
+			p.free();
+		}
+		self.copy.free();
+		self.quad.free();
+	}
+}
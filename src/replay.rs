@@ -0,0 +1,202 @@
+extern crate sdl2;
+
+use std::fmt;
+use std::fs::File;
+use std::io::{ self, Read, Write, BufWriter };
+use std::path::Path;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+
+use app::Application;
+use input::{ Modifiers, mouse_button_name, mouse_button_from_name };
+
+/// One input callback, as delivered to an `Application`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Input {
+	Resize(f32, f32),
+	KeyPress(Keycode, Modifiers),
+	MouseClick(MouseButton, f32, f32, Modifiers),
+	MouseRelease(MouseButton),
+	MouseMove(f32, f32),
+	MouseDrag(MouseButton, f32, f32),
+	MouseWheel(i32)
+}
+
+impl Input {
+	pub fn send<A: Application>(&self, app: &mut A) {
+		match *self {
+			Input::Resize(w, h) => app.on_resize(w, h),
+			Input::KeyPress(k, m) => app.on_key_press(k, m),
+			Input::MouseClick(b, x, y, m) => app.on_mouse_click(b, x, y, m),
+			Input::MouseRelease(b) => app.on_mouse_release(b),
+			Input::MouseMove(x, y) => app.on_mouse_move(x, y),
+			Input::MouseDrag(b, x, y) => app.on_mouse_drag(b, x, y),
+			Input::MouseWheel(a) => app.on_mouse_wheel(a)
+		}
+	}
+
+	fn parse(args: &[&str]) -> Option<Input> {
+		fn num(s: &str) -> Option<f32> { s.parse().ok() }
+		fn mods(s: &str) -> Modifiers {
+			Modifiers { ctrl: s.contains('C'), shift: s.contains('S'), alt: s.contains('A') }
+		}
+
+		match args {
+			&["resize", w, h] => Some(Input::Resize(num(w)?, num(h)?)),
+			&["key", k, m] => Some(Input::KeyPress(Keycode::from_i32(k.parse().ok()?)?, mods(m))),
+			&["click", b, x, y, m] => Some(Input::MouseClick(mouse_button_from_name(b)?, num(x)?, num(y)?, mods(m))),
+			&["release", b] => Some(Input::MouseRelease(mouse_button_from_name(b)?)),
+			&["move", x, y] => Some(Input::MouseMove(num(x)?, num(y)?)),
+			&["drag", b, x, y] => Some(Input::MouseDrag(mouse_button_from_name(b)?, num(x)?, num(y)?)),
+			&["wheel", a] => Some(Input::MouseWheel(a.parse().ok()?)),
+			_ => None
+		}
+	}
+}
+
+struct ModFlags(Modifiers);
+
+impl fmt::Display for ModFlags {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let m = self.0;
+		if !m.ctrl && !m.shift && !m.alt { return write!(f, "-"); }
+		if m.ctrl { write!(f, "C")?; }
+		if m.shift { write!(f, "S")?; }
+		if m.alt { write!(f, "A")?; }
+		Ok(())
+	}
+}
+
+impl fmt::Display for Input {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Input::Resize(w, h) => write!(f, "resize {:?} {:?}", w, h),
+			Input::KeyPress(k, m) => write!(f, "key {} {}", k as i32, ModFlags(m)),
+			Input::MouseClick(b, x, y, m) => write!(f, "click {} {:?} {:?} {}", mouse_button_name(b), x, y, ModFlags(m)),
+			Input::MouseRelease(b) => write!(f, "release {}", mouse_button_name(b)),
+			Input::MouseMove(x, y) => write!(f, "move {:?} {:?}", x, y),
+			Input::MouseDrag(b, x, y) => write!(f, "drag {} {:?} {:?}", mouse_button_name(b), x, y),
+			Input::MouseWheel(a) => write!(f, "wheel {}", a)
+		}
+	}
+}
+
+/// Writes everything needed to replay a session: the viewport, each input
+/// tagged with the simulation tick it arrived on, and how many ticks ran
+/// before each render.
+///
+/// Lines starting with `@` hold a free-form header, e.g. the options the
+/// session was started with.
+pub struct Recorder {
+	out: BufWriter<File>
+}
+
+impl Recorder {
+	pub fn create(path: &Path, header: &str) -> io::Result<Recorder> {
+		let mut out = BufWriter::new(File::create(path)?);
+		for line in header.lines() {
+			writeln!(out, "@{}", line)?;
+		}
+		Ok(Recorder { out: out })
+	}
+
+	pub fn init(&mut self, w: f32, h: f32) {
+		self.write(format_args!("init {:?} {:?}", w, h));
+	}
+
+	pub fn input(&mut self, tick: u64, input: &Input) {
+		self.write(format_args!("{} {}", tick, input));
+	}
+
+	/// Ends a frame: `ticks` updates were run, then a render with `alpha`.
+	pub fn frame(&mut self, ticks: u32, alpha: f32) {
+		self.write(format_args!("frame {} {:?}", ticks, alpha));
+		// Flush every frame so a recording survives a crash
+		let _ = self.out.flush();
+	}
+
+	fn write(&mut self, line: fmt::Arguments) {
+		if let Err(e) = writeln!(self.out, "{}", line) {
+			warn!("Could not write recording: {}", e);
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayFrame {
+	pub inputs: Vec<Input>,
+	pub ticks: u32,
+	pub alpha: f32
+}
+
+/// A recording loaded back for playback, one frame at a time.
+#[derive(Debug, Clone)]
+pub struct Replay {
+	pub header: String,
+	pub size: (f32, f32),
+	frames: Vec<ReplayFrame>,
+	current: usize
+}
+
+impl Replay {
+	pub fn load(path: &Path) -> Result<Replay, String> {
+		let mut source = String::new();
+		File::open(path)
+			.and_then(|mut f| f.read_to_string(&mut source))
+			.map_err(|e| format!("{}: {}", path.display(), e))?;
+
+		let mut header = String::new();
+		let mut size = None;
+		let mut frames = Vec::new();
+		let mut inputs = Vec::new();
+
+		for (i, line) in source.lines().enumerate() {
+			if line.starts_with('@') {
+				header.push_str(&line[1..]);
+				header.push('\n');
+				continue;
+			}
+
+			let err = || format!("{}:{}: invalid record '{}'", path.display(), i + 1, line);
+			let args: Vec<&str> = line.split_whitespace().collect();
+			match args.as_slice() {
+				&[] => {},
+				&["init", w, h] => {
+					size = Some((w.parse().map_err(|_| err())?, h.parse().map_err(|_| err())?));
+				},
+				&["frame", ticks, alpha] => {
+					frames.push(ReplayFrame {
+						inputs: inputs,
+						ticks: ticks.parse().map_err(|_| err())?,
+						alpha: alpha.parse().map_err(|_| err())?
+					});
+					inputs = Vec::new();
+				},
+				_ => {
+					// Inputs are prefixed by their tick, which is only there for readers
+					match Input::parse(&args[1..]) {
+						Some(input) => inputs.push(input),
+						None => return Err(err())
+					}
+				}
+			}
+		}
+
+		match size {
+			Some(size) => Ok(Replay { header: header, size: size, frames: frames, current: 0 }),
+			None => Err(format!("{}: missing `init` record", path.display()))
+		}
+	}
+
+	pub fn next_frame(&mut self) -> Option<&ReplayFrame> {
+		let frame = self.frames.get(self.current);
+		if frame.is_some() {
+			self.current += 1;
+		}
+		frame
+	}
+
+	pub fn frame_count(&self) -> usize {
+		self.frames.len()
+	}
+}
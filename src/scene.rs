@@ -0,0 +1,316 @@
+use vecmath::*;
+
+/// Handle to a node in a `Scene`. Handles of removed nodes may be reused.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// A transform relative to the node's parent, plus whatever the application
+/// wants drawn there.
+pub struct Node<D> {
+	position: Vec3,
+	rotation: Quat,
+	scale: Vec3,
+	/// Hiding a node hides its whole subtree
+	pub visible: bool,
+	pub drawable: Option<D>,
+	/// Local-space box used for culling. Nodes without one are always drawn
+	pub bounds: Option<(Vec3, Vec3)>,
+	parent: Option<NodeId>,
+	children: Vec<NodeId>,
+	world: Mat4,
+	dirty: bool
+}
+
+impl<D> Node<D> {
+	fn new(drawable: Option<D>, parent: Option<NodeId>) -> Node<D> {
+		Node {
+			position: Vec3::zero(),
+			rotation: Quat::identity(),
+			scale: Vec3::new(1.0, 1.0, 1.0),
+			visible: true,
+			drawable: drawable,
+			bounds: None,
+			parent: parent,
+			children: Vec::new(),
+			world: Mat4::identity(),
+			dirty: true
+		}
+	}
+
+	pub fn position(&self) -> Vec3 { self.position }
+	pub fn rotation(&self) -> Quat { self.rotation }
+	pub fn scale(&self) -> Vec3 { self.scale }
+
+	pub fn set_position(&mut self, p: Vec3) {
+		self.position = p;
+		self.dirty = true;
+	}
+
+	pub fn set_rotation(&mut self, r: Quat) {
+		self.rotation = r;
+		self.dirty = true;
+	}
+
+	pub fn set_scale(&mut self, s: Vec3) {
+		self.scale = s;
+		self.dirty = true;
+	}
+
+	/// Translation * rotation * scale, relative to the parent.
+	pub fn local(&self) -> Mat4 {
+		Mat4::translation(self.position) * self.rotation.to_mat4() * Mat4::scaling(self.scale)
+	}
+
+	/// World matrix as of the last `Scene::update`.
+	pub fn world(&self) -> Mat4 {
+		self.world
+	}
+}
+
+/// How many drawables a `Scene::draw_culled` call submitted and skipped.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct DrawStats {
+	pub drawn: usize,
+	pub culled: usize
+}
+
+/// Transform hierarchy. World matrices are cached and only recomputed for
+/// nodes that moved, or whose ancestors did.
+pub struct Scene<D> {
+	nodes: Vec<Option<Node<D>>>,
+	free: Vec<usize>,
+	roots: Vec<NodeId>
+}
+
+impl<D> Scene<D> {
+	pub fn new() -> Scene<D> {
+		Scene { nodes: Vec::new(), free: Vec::new(), roots: Vec::new() }
+	}
+
+	fn insert(&mut self, node: Node<D>) -> NodeId {
+		match self.free.pop() {
+			Some(i) => {
+				self.nodes[i] = Some(node);
+				NodeId(i)
+			},
+			None => {
+				self.nodes.push(Some(node));
+				NodeId(self.nodes.len() - 1)
+			}
+		}
+	}
+
+	/// Adds a node at the top level.
+	pub fn add(&mut self, drawable: Option<D>) -> NodeId {
+		let id = self.insert(Node::new(drawable, None));
+		self.roots.push(id);
+		id
+	}
+
+	pub fn add_child(&mut self, parent: NodeId, drawable: Option<D>) -> NodeId {
+		let id = self.insert(Node::new(drawable, Some(parent)));
+		self.node_mut(parent).children.push(id);
+		id
+	}
+
+	/// Removes the node and everything attached to it.
+	pub fn remove(&mut self, id: NodeId) {
+		self.detach(id);
+		let mut stack = vec![id];
+		while let Some(NodeId(i)) = stack.pop() {
+			if let Some(node) = self.nodes[i].take() {
+				stack.extend(node.children);
+				self.free.push(i);
+			}
+		}
+	}
+
+	fn detach(&mut self, id: NodeId) {
+		match self.node(id).parent {
+			Some(p) => self.node_mut(p).children.retain(|&c| c != id),
+			None => self.roots.retain(|&c| c != id)
+		}
+	}
+
+	/// Moves `id` under `parent`, or to the top level for `None`. Its local
+	/// transform is kept, so it will move along with its new parent.
+	pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) {
+		let mut p = parent;
+		while let Some(a) = p {
+			assert!(a != id, "a node can't be parented to its own descendant");
+			p = self.node(a).parent;
+		}
+
+		self.detach(id);
+		match parent {
+			Some(p) => self.node_mut(p).children.push(id),
+			None => self.roots.push(id)
+		}
+		let node = self.node_mut(id);
+		node.parent = parent;
+		node.dirty = true;
+	}
+
+	pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+		self.node(id).parent
+	}
+
+	pub fn children(&self, id: NodeId) -> &[NodeId] {
+		&self.node(id).children
+	}
+
+	pub fn node(&self, id: NodeId) -> &Node<D> {
+		self.nodes[id.0].as_ref().expect("node was removed")
+	}
+
+	pub fn node_mut(&mut self, id: NodeId) -> &mut Node<D> {
+		self.nodes[id.0].as_mut().expect("node was removed")
+	}
+
+	pub fn len(&self) -> usize {
+		self.nodes.len() - self.free.len()
+	}
+
+	/// Recomputes the world matrices of dirty nodes and their descendants.
+	pub fn update(&mut self) {
+		let mut stack: Vec<(NodeId, Mat4, bool)> = self.roots.iter().rev()
+			.map(|&id| (id, Mat4::identity(), false))
+			.collect();
+
+		while let Some((id, parent, parent_changed)) = stack.pop() {
+			let node = self.nodes[id.0].as_mut().unwrap();
+			let changed = node.dirty || parent_changed;
+			if changed {
+				node.world = parent * node.local();
+				node.dirty = false;
+			}
+			for &c in node.children.iter().rev() {
+				stack.push((c, node.world, changed));
+			}
+		}
+	}
+
+	/// World matrix of `id`, bringing the scene up to date first.
+	pub fn world(&mut self, id: NodeId) -> Mat4 {
+		self.update();
+		self.node(id).world
+	}
+
+	/// Updates the scene and calls `submit` for every visible drawable with
+	/// its world matrix, parents before children.
+	pub fn draw<F: FnMut(&D, Mat4)>(&mut self, submit: F) {
+		self.traverse(None, submit);
+	}
+
+	/// Like `draw`, but skips drawables whose bounds are outside `frustum`.
+	/// Children are tested on their own, so they may stick out of a culled parent.
+	pub fn draw_culled<F: FnMut(&D, Mat4)>(&mut self, frustum: &Frustum<f32>, submit: F) -> DrawStats {
+		self.traverse(Some(frustum), submit)
+	}
+
+	fn traverse<F: FnMut(&D, Mat4)>(&mut self, frustum: Option<&Frustum<f32>>, mut submit: F) -> DrawStats {
+		self.update();
+
+		let mut stats = DrawStats::default();
+		let mut stack: Vec<NodeId> = self.roots.iter().rev().cloned().collect();
+		while let Some(id) = stack.pop() {
+			let node = self.node(id);
+			if !node.visible { continue; }
+			if let Some(ref d) = node.drawable {
+				let inside = match (frustum, node.bounds) {
+					(Some(f), Some((min, max))) => {
+						let (min, max) = node.world.transform_aabb(min, max);
+						f.intersects_aabb(min, max)
+					},
+					_ => true
+				};
+				if inside {
+					submit(d, node.world);
+					stats.drawn += 1;
+				} else {
+					stats.culled += 1;
+				}
+			}
+			stack.extend(node.children.iter().rev());
+		}
+		stats
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::f32::consts::PI;
+	use super::*;
+
+	fn assert_mat4(a: Mat4, b: Mat4) {
+		assert!(a.approx_eq(b, 1e-5), "{:?} != {:?}", a, b);
+	}
+
+	#[test]
+	fn children_follow_parents() {
+		let mut scene: Scene<&str> = Scene::new();
+		let car = scene.add(Some("car"));
+		let light = scene.add_child(car, Some("light"));
+		scene.node_mut(light).set_position(Vec3::new(0.1, 0.0, -0.2));
+		scene.node_mut(car).set_position(Vec3::new(5.0, 0.0, 3.0));
+		scene.node_mut(car).set_rotation(Quat::axis_angle(Vec3::new(0.0, 1.0, 0.0), PI / 2.0));
+
+		let expected = scene.node(car).local() * scene.node(light).local();
+		assert_mat4(scene.world(light), expected);
+
+		// Moving only the parent still updates the child
+		scene.node_mut(car).set_position(Vec3::new(1.0, 0.0, 1.0));
+		let expected = scene.node(car).local() * scene.node(light).local();
+		assert_mat4(scene.world(light), expected);
+
+		let mut drawn = Vec::new();
+		scene.draw(|d, _| drawn.push(*d));
+		assert_eq!(drawn, vec!["car", "light"]);
+
+		scene.node_mut(car).visible = false;
+		drawn.clear();
+		scene.draw(|d, _| drawn.push(*d));
+		assert!(drawn.is_empty());
+	}
+
+	#[test]
+	fn culling_skips_offscreen_nodes() {
+		let mut scene: Scene<u32> = Scene::new();
+		let bounds = Some((Vec3::new(-0.5, -0.5, -0.5), Vec3::new(0.5, 0.5, 0.5)));
+		for i in 0..4 {
+			let id = scene.add(Some(i));
+			scene.node_mut(id).set_position(Vec3::new(i as f32 * 1.2, 0.0, -5.0));
+			scene.node_mut(id).bounds = bounds;
+		}
+		// Unbounded nodes are never culled
+		let far = scene.add(Some(9));
+		scene.node_mut(far).set_position(Vec3::new(100.0, 0.0, -5.0));
+
+		let frustum = Frustum::from_matrix(Mat4::ortho(-1.0, 1.0, -1.0, 1.0, 0.0, 10.0));
+		let mut drawn = Vec::new();
+		let stats = scene.draw_culled(&frustum, |d, _| drawn.push(*d));
+		assert_eq!(drawn, vec![0, 1, 9]);
+		assert_eq!(stats, DrawStats { drawn: 3, culled: 2 });
+	}
+
+	#[test]
+	fn reparent_and_remove() {
+		let mut scene: Scene<u32> = Scene::new();
+		let a = scene.add(Some(1));
+		let b = scene.add(Some(2));
+		let c = scene.add_child(a, Some(3));
+		scene.node_mut(a).set_position(Vec3::new(1.0, 0.0, 0.0));
+		scene.node_mut(b).set_position(Vec3::new(0.0, 2.0, 0.0));
+
+		scene.set_parent(c, Some(b));
+		assert_eq!(scene.parent(c), Some(b));
+		assert!(scene.children(a).is_empty());
+		assert_mat4(scene.world(c), Mat4::translation(Vec3::new(0.0, 2.0, 0.0)));
+
+		scene.remove(b);
+		assert_eq!(scene.len(), 1);
+		let d = scene.add(Some(4));
+		assert_eq!(scene.children(d).len(), 0);
+		assert_eq!(scene.len(), 2);
+	}
+}
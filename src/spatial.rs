@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use vecmath::*;
+
+/// Uniform grid bucketing items by the cell they stand on. With a cell size
+/// of 1 the cells line up with map tiles, so `(x, y)` is a tile coordinate.
+pub struct SpatialHash<K> {
+	cell_size: f32,
+	cells: HashMap<(i32, i32), Vec<K>>,
+	items: HashMap<K, (Vec2, (i32, i32))>
+}
+
+impl<K: Copy + Eq + Hash> SpatialHash<K> {
+	pub fn new(cell_size: f32) -> SpatialHash<K> {
+		assert!(cell_size > 0.0, "cell size must be positive");
+		SpatialHash { cell_size: cell_size, cells: HashMap::new(), items: HashMap::new() }
+	}
+
+	pub fn cell(&self, p: Vec2) -> (i32, i32) {
+		((p.x / self.cell_size).floor() as i32, (p.y / self.cell_size).floor() as i32)
+	}
+
+	/// Adds `key` at `pos`, or moves it there if it's already tracked.
+	pub fn insert(&mut self, key: K, pos: Vec2) {
+		let cell = self.cell(pos);
+		match self.items.insert(key, (pos, cell)) {
+			Some((_, old)) if old == cell => return,
+			Some((_, old)) => self.unlink(key, old),
+			None => {}
+		}
+		self.cells.entry(cell).or_insert_with(Vec::new).push(key);
+	}
+
+	pub fn remove(&mut self, key: K) {
+		if let Some((_, cell)) = self.items.remove(&key) {
+			self.unlink(key, cell);
+		}
+	}
+
+	fn unlink(&mut self, key: K, cell: (i32, i32)) {
+		let empty = match self.cells.get_mut(&cell) {
+			Some(keys) => {
+				if let Some(i) = keys.iter().position(|&k| k == key) {
+					keys.swap_remove(i);
+				}
+				keys.is_empty()
+			},
+			None => false
+		};
+		// Drop empty cells so the map doesn't grow with everywhere ever visited
+		if empty { self.cells.remove(&cell); }
+	}
+
+	pub fn clear(&mut self) {
+		self.cells.clear();
+		self.items.clear();
+	}
+
+	pub fn len(&self) -> usize {
+		self.items.len()
+	}
+
+	pub fn position(&self, key: K) -> Option<Vec2> {
+		self.items.get(&key).map(|&(p, _)| p)
+	}
+
+	/// Everything in cell `(x, y)`, in no particular order.
+	pub fn at(&self, x: i32, y: i32) -> &[K] {
+		match self.cells.get(&(x, y)) {
+			Some(keys) => keys,
+			None => &[]
+		}
+	}
+
+	/// Everything within `radius` of `center`, closest first.
+	pub fn query_radius(&self, center: Vec2, radius: f32) -> Vec<K> {
+		let (x0, y0) = self.cell(center - Vec2::new(radius, radius));
+		let (x1, y1) = self.cell(center + Vec2::new(radius, radius));
+
+		let mut found = Vec::new();
+		for y in y0..y1 + 1 {
+			for x in x0..x1 + 1 {
+				for &k in self.at(x, y) {
+					let d = self.items[&k].0.distance(center);
+					if d <= radius { found.push((k, d)); }
+				}
+			}
+		}
+		found.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+		found.into_iter().map(|(k, _)| k).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn tracks_moving_items() {
+		let mut grid = SpatialHash::new(1.0);
+		grid.insert(1, Vec2::new(0.5, 0.5));
+		grid.insert(2, Vec2::new(0.9, 0.2));
+		grid.insert(3, Vec2::new(-0.5, 2.5));
+		assert_eq!(grid.cell(Vec2::new(-0.5, 2.5)), (-1, 2));
+
+		let mut tile = grid.at(0, 0).to_vec();
+		tile.sort();
+		assert_eq!(tile, vec![1, 2]);
+		assert_eq!(grid.at(-1, 2), &[3]);
+
+		grid.insert(2, Vec2::new(1.1, 0.2));
+		assert_eq!(grid.at(0, 0), &[1]);
+		assert_eq!(grid.at(1, 0), &[2]);
+		assert_eq!(grid.position(2), Some(Vec2::new(1.1, 0.2)));
+
+		grid.remove(1);
+		assert!(grid.at(0, 0).is_empty());
+		assert_eq!(grid.len(), 2);
+	}
+
+	#[test]
+	fn radius_query_matches_brute_force() {
+		let mut grid = SpatialHash::new(1.0);
+		let mut points = Vec::new();
+		for i in 0..200 {
+			// Scattered deterministically over a 10x10 area, including negatives
+			let p = Vec2::new((i * 37 % 101) as f32 / 10.0 - 2.0, (i * 53 % 97) as f32 / 10.0 - 2.0);
+			grid.insert(i, p);
+			points.push(p);
+		}
+
+		let center = Vec2::new(3.3, 2.7);
+		let found = grid.query_radius(center, 1.7);
+		let mut expected: Vec<_> = (0..200).filter(|&i| points[i].distance(center) <= 1.7).collect();
+		let mut sorted = found.clone();
+		sorted.sort();
+		expected.sort();
+		assert_eq!(sorted, expected);
+		assert!(found.windows(2).all(|w| points[w[0]].distance(center) <= points[w[1]].distance(center)));
+	}
+}
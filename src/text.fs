@@ -0,0 +1,11 @@
+#version 330
+out vec4 fragColor;
+
+in vec2 uv;
+in vec4 color;
+
+uniform sampler2D texture0;
+
+void main() {
+	fragColor = texture(texture0, uv) * color;
+}
\ No newline at end of file
@@ -0,0 +1,329 @@
+use std::f32::consts::PI;
+use vecmath::*;
+
+/// Easing curves, mapping linear progress in `[0, 1]` to eased progress.
+/// `Elastic` and `Back` overshoot, so values briefly leave that range.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Ease {
+	Linear,
+	QuadIn,
+	QuadOut,
+	QuadInOut,
+	CubicIn,
+	CubicOut,
+	CubicInOut,
+	ElasticIn,
+	ElasticOut,
+	ElasticInOut,
+	BackIn,
+	BackOut,
+	BackInOut,
+	BounceIn,
+	BounceOut,
+	BounceInOut
+}
+
+const BACK: f32 = 1.70158;
+
+fn elastic_out(t: f32) -> f32 {
+	if t <= 0.0 || t >= 1.0 { return t.max(0.0).min(1.0); }
+	2.0f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+}
+
+fn bounce_out(t: f32) -> f32 {
+	const N: f32 = 7.5625;
+	const D: f32 = 2.75;
+	if t < 1.0 / D {
+		N * t * t
+	} else if t < 2.0 / D {
+		let t = t - 1.5 / D;
+		N * t * t + 0.75
+	} else if t < 2.5 / D {
+		let t = t - 2.25 / D;
+		N * t * t + 0.9375
+	} else {
+		let t = t - 2.625 / D;
+		N * t * t + 0.984375
+	}
+}
+
+fn back_in(t: f32) -> f32 {
+	t * t * ((BACK + 1.0) * t - BACK)
+}
+
+/// Builds the in-out variant of a curve from its ease-in half.
+fn in_out<F: Fn(f32) -> f32>(ease_in: F, t: f32) -> f32 {
+	if t < 0.5 {
+		ease_in(t * 2.0) / 2.0
+	} else {
+		1.0 - ease_in((1.0 - t) * 2.0) / 2.0
+	}
+}
+
+impl Ease {
+	pub fn apply(&self, t: f32) -> f32 {
+		let t = t.max(0.0).min(1.0);
+		match *self {
+			Ease::Linear => t,
+			Ease::QuadIn => t * t,
+			Ease::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+			Ease::QuadInOut => in_out(|t| t * t, t),
+			Ease::CubicIn => t * t * t,
+			Ease::CubicOut => 1.0 - (1.0 - t).powi(3),
+			Ease::CubicInOut => in_out(|t| t * t * t, t),
+			Ease::ElasticIn => 1.0 - elastic_out(1.0 - t),
+			Ease::ElasticOut => elastic_out(t),
+			Ease::ElasticInOut => in_out(|t| 1.0 - elastic_out(1.0 - t), t),
+			Ease::BackIn => back_in(t),
+			Ease::BackOut => 1.0 - back_in(1.0 - t),
+			Ease::BackInOut => in_out(back_in, t),
+			Ease::BounceIn => 1.0 - bounce_out(1.0 - t),
+			Ease::BounceOut => bounce_out(t),
+			Ease::BounceInOut => in_out(|t| 1.0 - bounce_out(1.0 - t), t)
+		}
+	}
+}
+
+/// Values a `Tween` can animate.
+pub trait Tweenable: Copy {
+	/// Blends from `a` at `t = 0` to `b` at `t = 1`. Must cope with `t`
+	/// slightly outside that range for overshooting curves.
+	fn tween(a: Self, b: Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+	fn tween(a: f32, b: f32, t: f32) -> f32 { a + (b - a) * t }
+}
+
+impl Tweenable for Vec2 {
+	fn tween(a: Vec2, b: Vec2, t: f32) -> Vec2 { a.lerp(b, t) }
+}
+
+impl Tweenable for Vec3 {
+	fn tween(a: Vec3, b: Vec3, t: f32) -> Vec3 { a.lerp(b, t) }
+}
+
+impl Tweenable for Vec4 {
+	fn tween(a: Vec4, b: Vec4, t: f32) -> Vec4 { a.lerp(b, t) }
+}
+
+impl Tweenable for Quat {
+	fn tween(a: Quat, b: Quat, t: f32) -> Quat { a.slerp(b, t) }
+}
+
+/// Animates a value from `from` to `to` over `duration` seconds.
+pub struct Tween<T: Tweenable> {
+	pub from: T,
+	/// Can be moved while running, e.g. to chase a moving target
+	pub to: T,
+	pub duration: f32,
+	pub ease: Ease,
+	elapsed: f32,
+	on_complete: Option<Box<FnMut()>>
+}
+
+impl<T: Tweenable> Tween<T> {
+	pub fn new(from: T, to: T, duration: f32, ease: Ease) -> Tween<T> {
+		Tween {
+			from: from,
+			to: to,
+			duration: duration,
+			ease: ease,
+			elapsed: 0.0,
+			on_complete: None
+		}
+	}
+
+	/// An already finished tween resting at `value`.
+	pub fn done(value: T) -> Tween<T> {
+		Tween::new(value, value, 0.0, Ease::Linear)
+	}
+
+	/// Calls `f` once, on the update that finishes the tween.
+	pub fn on_complete<F: FnMut() + 'static>(mut self, f: F) -> Tween<T> {
+		self.on_complete = Some(Box::new(f));
+		self
+	}
+
+	pub fn value(&self) -> T {
+		T::tween(self.from, self.to, self.ease.apply(self.progress()))
+	}
+
+	pub fn progress(&self) -> f32 {
+		if self.duration <= 0.0 { 1.0 } else { (self.elapsed / self.duration).min(1.0) }
+	}
+
+	pub fn finished(&self) -> bool {
+		self.elapsed >= self.duration
+	}
+
+	pub fn restart(&mut self) {
+		self.elapsed = 0.0;
+	}
+
+	/// Advances by `dt` seconds and returns the new value.
+	pub fn update(&mut self, dt: f32) -> T {
+		self.advance(dt);
+		self.value()
+	}
+
+	/// Advances by `dt` and returns the part of it left over after finishing.
+	fn advance(&mut self, dt: f32) -> f32 {
+		if self.finished() { return dt; }
+
+		self.elapsed += dt;
+		if !self.finished() { return 0.0; }
+
+		if let Some(ref mut f) = self.on_complete { f(); }
+		self.elapsed - self.duration
+	}
+}
+
+/// Tweens played one after another, each starting where the last one ended.
+pub struct Sequence<T: Tweenable> {
+	start: T,
+	steps: Vec<Tween<T>>,
+	current: usize,
+	looping: bool,
+	on_complete: Option<Box<FnMut()>>
+}
+
+impl<T: Tweenable> Sequence<T> {
+	pub fn new(start: T) -> Sequence<T> {
+		Sequence { start: start, steps: Vec::new(), current: 0, looping: false, on_complete: None }
+	}
+
+	/// Appends a step from the previous step's end to `value`.
+	pub fn to(self, value: T, duration: f32, ease: Ease) -> Sequence<T> {
+		let from = self.end();
+		self.then(Tween::new(from, value, duration, ease))
+	}
+
+	/// Appends a step holding the current end value for `duration`.
+	pub fn wait(self, duration: f32) -> Sequence<T> {
+		let v = self.end();
+		self.then(Tween::new(v, v, duration, Ease::Linear))
+	}
+
+	pub fn then(mut self, tween: Tween<T>) -> Sequence<T> {
+		self.steps.push(tween);
+		self
+	}
+
+	/// Starts over after the last step instead of finishing.
+	pub fn looped(mut self) -> Sequence<T> {
+		self.looping = true;
+		self
+	}
+
+	/// Calls `f` once the last step finishes. Looping sequences never do.
+	pub fn on_complete<F: FnMut() + 'static>(mut self, f: F) -> Sequence<T> {
+		self.on_complete = Some(Box::new(f));
+		self
+	}
+
+	fn end(&self) -> T {
+		self.steps.last().map(|s| s.to).unwrap_or(self.start)
+	}
+
+	pub fn value(&self) -> T {
+		match self.steps.get(self.current) {
+			Some(step) => step.value(),
+			None => self.end()
+		}
+	}
+
+	pub fn finished(&self) -> bool {
+		self.current >= self.steps.len()
+	}
+
+	pub fn restart(&mut self) {
+		self.current = 0;
+		for step in self.steps.iter_mut() {
+			step.restart();
+		}
+	}
+
+	pub fn update(&mut self, dt: f32) -> T {
+		let mut dt = dt;
+		let mut wrapped = false;
+		while !self.finished() {
+			dt = self.steps[self.current].advance(dt);
+			if !self.steps[self.current].finished() { break; }
+
+			self.current += 1;
+			if !self.finished() { continue; }
+
+			if !self.looping {
+				if let Some(ref mut f) = self.on_complete { f(); }
+			} else {
+				self.restart();
+				// Wrap at most once per update so empty steps can't spin forever
+				if wrapped { break; }
+				wrapped = true;
+			}
+		}
+		self.value()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::rc::Rc;
+	use std::cell::Cell;
+	use super::*;
+
+	const EASES: &'static [Ease] = &[
+		Ease::Linear, Ease::QuadIn, Ease::QuadOut, Ease::QuadInOut, Ease::CubicIn, Ease::CubicOut,
+		Ease::CubicInOut, Ease::ElasticIn, Ease::ElasticOut, Ease::ElasticInOut, Ease::BackIn,
+		Ease::BackOut, Ease::BackInOut, Ease::BounceIn, Ease::BounceOut, Ease::BounceInOut
+	];
+
+	#[test]
+	fn eases_start_and_end_in_place() {
+		for e in EASES {
+			assert!(e.apply(0.0).abs() < 1e-5, "{:?}(0) = {}", e, e.apply(0.0));
+			assert!((e.apply(1.0) - 1.0).abs() < 1e-5, "{:?}(1) = {}", e, e.apply(1.0));
+		}
+		assert!((Ease::CubicInOut.apply(0.5) - 0.5).abs() < 1e-6);
+		assert!(Ease::BackIn.apply(0.2) < 0.0);
+		assert!(Ease::BackOut.apply(0.8) > 1.0);
+		assert!((Ease::QuadOut.apply(0.5) - 0.75).abs() < 1e-6);
+	}
+
+	#[test]
+	fn tween_fires_callback_once() {
+		let count = Rc::new(Cell::new(0));
+		let c = count.clone();
+		let mut t = Tween::new(Vec2::new(0.0, 0.0), Vec2::new(2.0, 4.0), 1.0, Ease::Linear)
+			.on_complete(move || c.set(c.get() + 1));
+
+		assert_eq!(t.update(0.25), Vec2::new(0.5, 1.0));
+		assert!(!t.finished());
+		assert_eq!(t.update(1.0), Vec2::new(2.0, 4.0));
+		t.update(1.0);
+		assert!(t.finished());
+		assert_eq!(count.get(), 1);
+	}
+
+	#[test]
+	fn sequence_carries_time_between_steps() {
+		let done = Rc::new(Cell::new(false));
+		let d = done.clone();
+		let mut s = Sequence::new(0.0)
+			.to(1.0, 1.0, Ease::Linear)
+			.wait(0.5)
+			.to(3.0, 1.0, Ease::Linear)
+			.on_complete(move || d.set(true));
+
+		assert_eq!(s.update(1.25), 1.0);
+		assert_eq!(s.update(0.5), 1.5);
+		assert!(!done.get());
+		assert_eq!(s.update(10.0), 3.0);
+		assert!(s.finished() && done.get());
+
+		let mut l = Sequence::new(0.0).to(1.0, 1.0, Ease::Linear).to(0.0, 1.0, Ease::Linear).looped();
+		assert_eq!(l.update(2.5), 0.5);
+		assert!(!l.finished());
+	}
+}
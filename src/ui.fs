@@ -0,0 +1,8 @@
+#version 330
+out vec4 fragColor;
+
+in vec4 color;
+
+void main() {
+	fragColor = color;
+}
\ No newline at end of file
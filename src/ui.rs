@@ -0,0 +1,252 @@
+extern crate gl;
+
+use vecmath::*;
+use renderer::*;
+
+const ROW_HEIGHT: f32 = 24.0;
+const PADDING: f32 = 6.0;
+
+const PANEL_COLOR: (f32, f32, f32, f32) = (0.08, 0.08, 0.12, 0.85);
+const WIDGET_COLOR: (f32, f32, f32, f32) = (0.22, 0.22, 0.3, 1.0);
+const HOT_COLOR: (f32, f32, f32, f32) = (0.3, 0.3, 0.42, 1.0);
+const ACTIVE_COLOR: (f32, f32, f32, f32) = (0.2, 0.45, 0.8, 1.0);
+const TEXT_COLOR: (f32, f32, f32, f32) = (1.0, 1.0, 1.0, 1.0);
+
+fn color(c: (f32, f32, f32, f32)) -> Vec4 {
+	Vec4::new(c.0, c.1, c.2, c.3)
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Rect { pub x: f32, pub y: f32, pub w: f32, pub h: f32 }
+
+impl Rect {
+	pub fn new(x: f32, y: f32, w: f32, h: f32) -> Rect {
+		Rect { x: x, y: y, w: w, h: h }
+	}
+
+	pub fn contains(&self, p: Vec2) -> bool {
+		p.x >= self.x && p.x < self.x + self.w && p.y >= self.y && p.y < self.y + self.h
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Align { Left, Center }
+
+struct Label {
+	text: String,
+	rect: Rect,
+	align: Align
+}
+
+struct Panel {
+	rect: Rect,
+	quad: usize,
+	cursor: f32
+}
+
+/// Immediate-mode GUI. Widgets are declared every frame between `begin` and
+/// `end`, and mouse events are fed in from the window so the UI can claim
+/// the clicks that land on it.
+pub struct Ui {
+	shader: Shader,
+	batch: Model,
+	quads: Vec<(Rect, Vec4)>,
+	labels: Vec<Label>,
+	panel: Option<Panel>,
+	blocking: Vec<Rect>,
+	mouse: Vec2,
+	down: bool,
+	pressed: bool,
+	released: bool,
+	next_id: u32,
+	active: Option<u32>
+}
+
+impl Ui {
+	pub fn new() -> Ui {
+		let shd = Shader::new();
+		shd.add_shader(include_str!("ui.vs"), gl::VERTEX_SHADER);
+		shd.add_shader(include_str!("ui.fs"), gl::FRAGMENT_SHADER);
+		shd.link();
+
+		let fmt = VertexFormat::new(&[
+			VertexAttribute::new(2, false),
+			VertexAttribute::new(4, false)
+		]);
+
+		Ui {
+			shader: shd,
+			batch: Model::new(fmt),
+			quads: Vec::new(),
+			labels: Vec::new(),
+			panel: None,
+			blocking: Vec::new(),
+			mouse: Vec2::zero(),
+			down: false,
+			pressed: false,
+			released: false,
+			next_id: 0,
+			active: None
+		}
+	}
+
+	pub fn mouse_move(&mut self, x: f32, y: f32) {
+		self.mouse = Vec2::new(x, y);
+	}
+
+	/// Returns true when the press landed on the UI and must not reach the game.
+	pub fn mouse_down(&mut self, x: f32, y: f32) -> bool {
+		self.mouse = Vec2::new(x, y);
+		if !self.wants_mouse() { return false; }
+		self.down = true;
+		self.pressed = true;
+		true
+	}
+
+	/// Returns true when the UI owned the press that is being released.
+	pub fn mouse_up(&mut self) -> bool {
+		let owned = self.down;
+		if owned { self.released = true; }
+		self.down = false;
+		owned
+	}
+
+	/// Whether the mouse is over a panel or dragging a widget.
+	pub fn wants_mouse(&self) -> bool {
+		self.down || self.blocking.iter().any(|r| r.contains(self.mouse))
+	}
+
+	pub fn begin(&mut self) {
+		self.quads.clear();
+		self.labels.clear();
+		self.blocking.clear();
+		self.next_id = 0;
+	}
+
+	pub fn begin_panel(&mut self, x: f32, y: f32, width: f32) {
+		let rect = Rect::new(x, y, width, PADDING);
+		self.quads.push((rect, color(PANEL_COLOR)));
+		self.panel = Some(Panel { rect: rect, quad: self.quads.len() - 1, cursor: y + PADDING });
+	}
+
+	pub fn end_panel(&mut self) {
+		if let Some(p) = self.panel.take() {
+			let mut rect = p.rect;
+			rect.h = p.cursor - rect.y;
+			self.quads[p.quad].0 = rect;
+			self.blocking.push(rect);
+		}
+	}
+
+	fn next_rect(&mut self) -> Rect {
+		match self.panel {
+			Some(ref mut p) => {
+				let r = Rect::new(p.rect.x + PADDING, p.cursor, p.rect.w - PADDING * 2.0, ROW_HEIGHT);
+				p.cursor += ROW_HEIGHT + PADDING;
+				r
+			},
+			None => { panic!("UI widgets must be placed inside a panel."); }
+		}
+	}
+
+	fn widget(&mut self) -> (u32, Rect, bool) {
+		let id = self.next_id;
+		self.next_id += 1;
+		let rect = self.next_rect();
+		let hot = rect.contains(self.mouse);
+		if hot && self.pressed {
+			self.active = Some(id);
+		}
+		(id, rect, hot)
+	}
+
+	pub fn label(&mut self, text: &str) {
+		let rect = self.next_rect();
+		self.labels.push(Label { text: text.to_owned(), rect: rect, align: Align::Left });
+	}
+
+	pub fn button(&mut self, text: &str) -> bool {
+		let (id, rect, hot) = self.widget();
+		let active = self.active == Some(id);
+		let col = if active && hot { ACTIVE_COLOR } else if hot { HOT_COLOR } else { WIDGET_COLOR };
+		self.quads.push((rect, color(col)));
+		self.labels.push(Label { text: text.to_owned(), rect: rect, align: Align::Center });
+		active && hot && self.released
+	}
+
+	/// A button that stays highlighted while `value` is set. Returns true when toggled.
+	pub fn toggle(&mut self, text: &str, value: &mut bool) -> bool {
+		let (id, rect, hot) = self.widget();
+		let active = self.active == Some(id);
+		let clicked = active && hot && self.released;
+		if clicked { *value = !*value; }
+
+		let col = if *value { ACTIVE_COLOR } else if hot { HOT_COLOR } else { WIDGET_COLOR };
+		self.quads.push((rect, color(col)));
+		self.labels.push(Label { text: text.to_owned(), rect: rect, align: Align::Center });
+		clicked
+	}
+
+	/// Horizontal slider. Returns true while the value is being changed.
+	pub fn slider(&mut self, text: &str, value: &mut f32, min: f32, max: f32) -> bool {
+		let (id, rect, hot) = self.widget();
+		let mut changed = false;
+		if self.active == Some(id) && self.down {
+			let t = ((self.mouse.x - rect.x) / rect.w).max(0.0).min(1.0);
+			let v = min + (max - min) * t;
+			changed = v != *value;
+			*value = v;
+		}
+
+		let t = ((*value - min) / (max - min)).max(0.0).min(1.0);
+		let fill = Rect::new(rect.x, rect.y, rect.w * t, rect.h);
+		self.quads.push((rect, color(if hot { HOT_COLOR } else { WIDGET_COLOR })));
+		self.quads.push((fill, color(ACTIVE_COLOR)));
+		self.labels.push(Label { text: text.to_owned(), rect: rect, align: Align::Center });
+		changed
+	}
+
+	/// Finishes the frame, queuing all widget labels on `text`.
+	pub fn end(&mut self, text: &mut TextRenderer) {
+		for l in self.labels.iter() {
+			let size = text.font.measure(&l.text);
+			let x = match l.align {
+				Align::Left => l.rect.x,
+				Align::Center => l.rect.x + ((l.rect.w - size.x) * 0.5).floor()
+			};
+			let y = l.rect.y + ((l.rect.h - size.y) * 0.5).floor();
+			text.text(&l.text, Vec2::new(x, y), color(TEXT_COLOR));
+		}
+
+		if self.released { self.active = None; }
+		self.pressed = false;
+		self.released = false;
+	}
+
+	pub fn render(&mut self, w: f32, h: f32) {
+		if self.quads.is_empty() { return; }
+
+		self.batch.clear();
+		for (i, &(r, c)) in self.quads.iter().enumerate() {
+			let base = (i * 4) as u16;
+			self.batch.add_data(&[
+				r.x, r.y, c.x, c.y, c.z, c.w,
+				r.x + r.w, r.y, c.x, c.y, c.z, c.w,
+				r.x + r.w, r.y + r.h, c.x, c.y, c.z, c.w,
+				r.x, r.y + r.h, c.x, c.y, c.z, c.w
+			], &[base, base + 1, base + 2, base + 2, base + 3, base]);
+		}
+		self.batch.flush();
+
+		GL!(Disable(gl::DEPTH_TEST));
+		self.shader.bind();
+		self.shader.get("projection").unwrap().set(Mat4::ortho(0.0, w, h, 0.0, -1.0, 1.0));
+		self.batch.draw(gl::TRIANGLES);
+		self.shader.unbind();
+		GL!(Enable(gl::DEPTH_TEST));
+	}
+
+	pub fn free(&mut self) {
+		self.batch.free();
+	}
+}
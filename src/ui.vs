@@ -0,0 +1,13 @@
+#version 330
+
+layout (location = 0) in vec2 v_pos;
+layout (location = 1) in vec4 v_col;
+
+out vec4 color;
+
+uniform mat4 projection;
+
+void main() {
+	gl_Position = projection * vec4(v_pos, 0.0, 1.0);
+	color = v_col;
+}
\ No newline at end of file
@@ -1,188 +1,388 @@
+use std::fmt;
 use std::ops::*;
+use num::Float;
+
+/// Scalar type the vector and matrix types are generic over. Simulation code
+/// can use `f64` while anything uploaded to the GPU stays `f32`.
+pub trait Scalar: Float + Default + fmt::Debug + AddAssign + SubAssign + MulAssign + DivAssign {
+	/// Converts a constant, rounding if `Self` is narrower than `f64`.
+	fn of(v: f64) -> Self;
+	fn as_f64(self) -> f64;
+}
+
+impl Scalar for f32 {
+	fn of(v: f64) -> f32 { v as f32 }
+	fn as_f64(self) -> f64 { self as f64 }
+}
+
+impl Scalar for f64 {
+	fn of(v: f64) -> f64 { v }
+	fn as_f64(self) -> f64 { self }
+}
+
+pub type Vec2 = Vector2<f32>;
+pub type Vec3 = Vector3<f32>;
+pub type Vec4 = Vector4<f32>;
+pub type Mat2 = Matrix2<f32>;
+pub type Mat3 = Matrix3<f32>;
+pub type Mat4 = Matrix4<f32>;
+pub type Quat = Quaternion<f32>;
+
+pub type DVec2 = Vector2<f64>;
+pub type DVec3 = Vector3<f64>;
+pub type DVec4 = Vector4<f64>;
+pub type DMat2 = Matrix2<f64>;
+pub type DMat3 = Matrix3<f64>;
+pub type DMat4 = Matrix4<f64>;
+pub type DQuat = Quaternion<f64>;
+
+/// Component-wise operators, helpers and conversions shared by the vector types.
+macro_rules! vec_impls {
+	($V:ident { $($f:ident),+ }, $n:expr, $Tup:ty) => {
+		impl<T: Scalar> $V<T> {
+			pub fn min(&self, o: $V<T>) -> $V<T> { $V { $($f: self.$f.min(o.$f)),+ } }
+			pub fn max(&self, o: $V<T>) -> $V<T> { $V { $($f: self.$f.max(o.$f)),+ } }
+			pub fn abs(&self) -> $V<T> { $V { $($f: self.$f.abs()),+ } }
+
+			pub fn lerp(&self, o: $V<T>, t: T) -> $V<T> {
+				*self + (o - *self) * t
+			}
+
+			pub fn distance(&self, o: $V<T>) -> T {
+				(o - *self).length()
+			}
+
+			/// True if every component is within `eps` of `o`'s.
+			pub fn approx_eq(&self, o: $V<T>, eps: T) -> bool {
+				true $(&& (self.$f - o.$f).abs() <= eps)+
+			}
+
+			/// Converts to another precision, rounding when narrowing.
+			pub fn cast<U: Scalar>(&self) -> $V<U> {
+				$V { $($f: U::of(self.$f.as_f64())),+ }
+			}
+		}
+
+		impl<T: Scalar> AddAssign<$V<T>> for $V<T> {
+			fn add_assign(&mut self, rhs: $V<T>) { $(self.$f += rhs.$f;)+ }
+		}
+
+		impl<T: Scalar> SubAssign<$V<T>> for $V<T> {
+			fn sub_assign(&mut self, rhs: $V<T>) { $(self.$f -= rhs.$f;)+ }
+		}
+
+		impl<T: Scalar> MulAssign<$V<T>> for $V<T> {
+			fn mul_assign(&mut self, rhs: $V<T>) { $(self.$f *= rhs.$f;)+ }
+		}
+
+		impl<T: Scalar> MulAssign<T> for $V<T> {
+			fn mul_assign(&mut self, rhs: T) { $(self.$f *= rhs;)+ }
+		}
+
+		impl Mul<$V<f32>> for f32 {
+			type Output = $V<f32>;
+			fn mul(self, rhs: $V<f32>) -> $V<f32> { rhs * self }
+		}
+
+		impl Mul<$V<f64>> for f64 {
+			type Output = $V<f64>;
+			fn mul(self, rhs: $V<f64>) -> $V<f64> { rhs * self }
+		}
+
+		impl<T: Scalar> Div<$V<T>> for $V<T> {
+			type Output = $V<T>;
+			fn div(self, rhs: $V<T>) -> $V<T> { $V { $($f: self.$f / rhs.$f),+ } }
+		}
+
+		impl<T: Scalar> Div<T> for $V<T> {
+			type Output = $V<T>;
+			fn div(self, rhs: T) -> $V<T> { $V { $($f: self.$f / rhs),+ } }
+		}
+
+		impl<T: Scalar> DivAssign<$V<T>> for $V<T> {
+			fn div_assign(&mut self, rhs: $V<T>) { $(self.$f /= rhs.$f;)+ }
+		}
+
+		impl<T: Scalar> DivAssign<T> for $V<T> {
+			fn div_assign(&mut self, rhs: T) { $(self.$f /= rhs;)+ }
+		}
+
+		impl<T: Scalar> From<[T; $n]> for $V<T> {
+			fn from(a: [T; $n]) -> $V<T> { $V::from_slice(&a) }
+		}
+
+		impl<T: Scalar> From<$V<T>> for [T; $n] {
+			fn from(v: $V<T>) -> [T; $n] { [$(v.$f),+] }
+		}
+
+		impl<T: Scalar> From<$Tup> for $V<T> {
+			fn from(t: $Tup) -> $V<T> {
+				let ($($f),+) = t;
+				$V { $($f: $f),+ }
+			}
+		}
+
+		impl<T: Scalar> From<$V<T>> for $Tup {
+			fn from(v: $V<T>) -> $Tup { ($(v.$f),+) }
+		}
+
+		// Widening is lossless, so it gets a plain `From`; narrowing goes through `cast`
+		impl From<$V<f32>> for $V<f64> {
+			fn from(v: $V<f32>) -> $V<f64> { v.cast() }
+		}
+	};
+}
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct Vec2 { pub x: f32, pub y: f32 }
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Vector2<T> { pub x: T, pub y: T }
 
-impl Vec2 {
-	pub fn new(x: f32, y: f32) -> Vec2 {
-		Vec2 { x: x, y: y }
+impl<T: Scalar> Vector2<T> {
+	pub fn new(x: T, y: T) -> Vector2<T> {
+		Vector2 { x: x, y: y }
 	}
 
-	pub fn zero() -> Vec2 { Vec2::new(0.0, 0.0) }
+	pub fn zero() -> Vector2<T> { Vector2::new(T::zero(), T::zero()) }
 
-	pub fn from_slice(s: &[f32]) -> Vec2 {
+	pub fn from_slice(s: &[T]) -> Vector2<T> {
 		assert!(s.len() >= 2);
-		Vec2::new(s[0], s[1])
+		Vector2::new(s[0], s[1])
 	}
 
-	pub fn rotate(&self, a: f32) -> Vec2 {
+	pub fn rotate(&self, a: T) -> Vector2<T> {
 		let (s, c) = a.sin_cos();
-		Vec2::new((c * self.x) - (s * self.y), (s * self.x) + (c * self.y))
+		Vector2::new((c * self.x) - (s * self.y), (s * self.x) + (c * self.y))
 	}
 
-	pub fn extend(&self, z: f32) -> Vec3 {
-		Vec3::new(self.x, self.y, z)
+	pub fn extend(&self, z: T) -> Vector3<T> {
+		Vector3::new(self.x, self.y, z)
 	}
 
-	pub fn dot(&self, other: Vec2) -> f32 {
+	pub fn dot(&self, other: Vector2<T>) -> T {
 		self.x * other.x + self.y * other.y
 	}
 
-	pub fn perp_dot(&self, other: Vec2) -> f32 {
+	pub fn perp_dot(&self, other: Vector2<T>) -> T {
 		self.x * other.y - self.y * other.x
 	}
 
-	pub fn length(self) -> f32 { 
+	/// Mirrors the vector about the line with unit normal `n`.
+	pub fn reflect(&self, n: Vector2<T>) -> Vector2<T> {
+		*self - n * (T::of(2.0) * self.dot(n))
+	}
+
+	pub fn length(self) -> T {
 		self.dot(self).sqrt()
 	}
 
-	pub fn normalized(&self) -> Vec2 {
+	pub fn normalized(&self) -> Vector2<T> {
 		let mut len = self.length();
-		if len <= 0.0 { len = 1.0; }
-		Vec2 { x: self.x / len, y: self.y / len }
+		if len <= T::zero() { len = T::one(); }
+		Vector2 { x: self.x / len, y: self.y / len }
 	}
 
 }
 
-impl Add<Vec2> for Vec2 {
-	type Output = Vec2;
-	fn add(self, rhs: Vec2) -> Vec2 {
-		Vec2 { x: self.x + rhs.x, y: self.y + rhs.y }
+impl<T: Scalar> Add<Vector2<T>> for Vector2<T> {
+	type Output = Vector2<T>;
+	fn add(self, rhs: Vector2<T>) -> Vector2<T> {
+		Vector2 { x: self.x + rhs.x, y: self.y + rhs.y }
 	}
 }
 
-impl Sub<Vec2> for Vec2 {
-	type Output = Vec2;
-	fn sub(self, rhs: Vec2) -> Vec2 {
-		Vec2 { x: self.x - rhs.x, y: self.y - rhs.y }
+impl<T: Scalar> Sub<Vector2<T>> for Vector2<T> {
+	type Output = Vector2<T>;
+	fn sub(self, rhs: Vector2<T>) -> Vector2<T> {
+		Vector2 { x: self.x - rhs.x, y: self.y - rhs.y }
 	}
 }
 
-impl Mul<Vec2> for Vec2 {
-	type Output = Vec2;
-	fn mul(self, rhs: Vec2) -> Vec2 {
-		Vec2 { x: self.x * rhs.x, y: self.y * rhs.y }
+impl<T: Scalar> Mul<Vector2<T>> for Vector2<T> {
+	type Output = Vector2<T>;
+	fn mul(self, rhs: Vector2<T>) -> Vector2<T> {
+		Vector2 { x: self.x * rhs.x, y: self.y * rhs.y }
 	}
 }
 
-impl Mul<f32> for Vec2 {
-	type Output = Vec2;
-	fn mul(self, rhs: f32) -> Vec2 {
-		Vec2 { x: self.x * rhs, y: self.y * rhs }
+impl<T: Scalar> Mul<T> for Vector2<T> {
+	type Output = Vector2<T>;
+	fn mul(self, rhs: T) -> Vector2<T> {
+		Vector2 { x: self.x * rhs, y: self.y * rhs }
+	}
+}
+
+impl<T: Scalar> Neg for Vector2<T> {
+	type Output = Vector2<T>;
+	fn neg(self) -> Vector2<T> {
+		Vector2 { x: -self.x, y: -self.y }
+	}
+}
+
+vec_impls!(Vector2 { x, y }, 2, (T, T));
+
+impl<T> Index<usize> for Vector2<T> {
+	type Output = T;
+	fn index(&self, i: usize) -> &T {
+		match i {
+			0 => { &self.x },
+			_ => { &self.y }
+		}
 	}
 }
 
-impl Neg for Vec2 {
-	type Output = Vec2;
-	fn neg(self) -> Vec2 {
-		Vec2 { x: -self.x, y: -self.y }
+impl<T> IndexMut<usize> for Vector2<T> {
+	fn index_mut(&mut self, i: usize) -> &mut T {
+		match i {
+			0 => { &mut self.x },
+			_ => { &mut self.y }
+		}
 	}
 }
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct Vec3 { pub x: f32, pub y: f32, pub z: f32 }
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Vector3<T> { pub x: T, pub y: T, pub z: T }
+
+impl<T: Scalar> Vector3<T> {
+	pub fn new(x: T, y: T, z: T) -> Vector3<T> {
+		Vector3 { x: x, y: y, z: z }
+	}
 
-impl Vec3 {
-	pub fn new(x: f32, y: f32, z: f32) -> Vec3 {
-		Vec3 { x: x, y: y, z: z }
+	/// Maps a point to window coordinates, with `viewport` given as
+	/// `(x, y, width, height)` and y growing downwards. Depth is in `[0, 1]`.
+	pub fn project(&self, viewport: Vector4<T>, model_view: Matrix4<T>, projection: Matrix4<T>) -> Vector3<T> {
+		let (one, half) = (T::one(), T::of(0.5));
+		let clip = projection * model_view * self.extend(one);
+		let ndc = clip.to_vec3() / clip.w;
+
+		Vector3::new(
+			viewport.x + (ndc.x + one) * half * viewport.z,
+			viewport.y + (one - ndc.y) * half * viewport.w,
+			(ndc.z + one) * half
+		)
 	}
 
-	pub fn unproject(&self, viewport: Vec4, model_view: Mat4, projection: Mat4) -> Vec3 {
+	/// Inverse of `project`.
+	pub fn unproject(&self, viewport: Vector4<T>, model_view: Matrix4<T>, projection: Matrix4<T>) -> Vector3<T> {
 		let invpv = (projection * model_view).inverted();
+		let (one, two) = (T::one(), T::of(2.0));
 
-		let w = viewport[2] - viewport[0];
-		let h = viewport[3] - viewport[1];
-		
-		let x = (2.0 * (self.x - viewport.x) / w) - 1.0;
-		let y = -((2.0 * (self.y - viewport.y) / h) - 1.0);
-		let z = 2.0 * self.z - 1.0;
+		let x = (two * (self.x - viewport.x) / viewport.z) - one;
+		let y = -((two * (self.y - viewport.y) / viewport.w) - one);
+		let z = two * self.z - one;
 
-		let r_cast = invpv * Vec4::new(x, y, z, 1.0);
-		Vec3::new(
+		let r_cast = invpv * Vector4::new(x, y, z, one);
+		Vector3::new(
 			r_cast.x / r_cast.w,
 			r_cast.y / r_cast.w,
 			r_cast.z / r_cast.w
 		)
 	}
 
-	pub fn zero() -> Vec3 { Vec3::new(0.0, 0.0, 0.0) }
+	pub fn zero() -> Vector3<T> { Vector3::new(T::zero(), T::zero(), T::zero()) }
 
-	pub fn from_slice(s: &[f32]) -> Vec3 {
+	pub fn from_slice(s: &[T]) -> Vector3<T> {
 		assert!(s.len() >= 3);
-		Vec3::new(s[0], s[1], s[2])
+		Vector3::new(s[0], s[1], s[2])
 	}
 
-	pub fn extend(&self, w: f32) -> Vec4 {
-		Vec4::new(self.x, self.y, self.z, w)
+	pub fn extend(&self, w: T) -> Vector4<T> {
+		Vector4::new(self.x, self.y, self.z, w)
 	}
 
-	pub fn dot(&self, other: Vec3) -> f32 {
+	pub fn dot(&self, other: Vector3<T>) -> T {
 		self.x * other.x + self.y * other.y + self.z * other.z
 	}
 
-	pub fn cross(self, other: Vec3) -> Vec3 {
-		Vec3 {
+	/// Mirrors the vector about the plane with unit normal `n`.
+	pub fn reflect(&self, n: Vector3<T>) -> Vector3<T> {
+		*self - n * (T::of(2.0) * self.dot(n))
+	}
+
+	pub fn cross(self, other: Vector3<T>) -> Vector3<T> {
+		Vector3 {
 			x: self.y * other.z - self.z * other.y,
 			y: self.z * other.x - self.x * other.z,
-			z: self.x * other.y - self.y * self.x
+			z: self.x * other.y - self.y * other.x
 		}
 	}
 
-	pub fn length(&self) -> f32 {
-		self.dot(self.clone()).sqrt()
+	pub fn length(&self) -> T {
+		self.dot(*self).sqrt()
 	}
 
-	pub fn normalized(&self) -> Vec3 {
+	pub fn normalized(&self) -> Vector3<T> {
 		let len = self.length();
-		Vec3 { x: self.x / len, y: self.y / len, z: self.z / len }
+		Vector3 { x: self.x / len, y: self.y / len, z: self.z / len }
+	}
+
+}
+
+impl<T: Scalar> Add<Vector3<T>> for Vector3<T> {
+	type Output = Vector3<T>;
+	fn add(self, rhs: Vector3<T>) -> Vector3<T> {
+		Vector3 { x: self.x + rhs.x, y: self.y + rhs.y, z: self.z + rhs.z }
 	}
+}
 
+impl<T: Scalar> Sub<Vector3<T>> for Vector3<T> {
+	type Output = Vector3<T>;
+	fn sub(self, rhs: Vector3<T>) -> Vector3<T> {
+		Vector3 { x: self.x - rhs.x, y: self.y - rhs.y, z: self.z - rhs.z }
+	}
 }
 
-impl Add<Vec3> for Vec3 {
-	type Output = Vec3;
-	fn add(self, rhs: Vec3) -> Vec3 {
-		Vec3 { x: self.x + rhs.x, y: self.y + rhs.y, z: self.z + rhs.z }
+impl<T: Scalar> Mul<Vector3<T>> for Vector3<T> {
+	type Output = Vector3<T>;
+	fn mul(self, rhs: Vector3<T>) -> Vector3<T> {
+		Vector3 { x: self.x * rhs.x, y: self.y * rhs.y, z: self.z * rhs.z }
 	}
 }
 
-impl Sub<Vec3> for Vec3 {
-	type Output = Vec3;
-	fn sub(self, rhs: Vec3) -> Vec3 {
-		Vec3 { x: self.x - rhs.x, y: self.y - rhs.y, z: self.z - rhs.z }
+impl<T: Scalar> Mul<T> for Vector3<T> {
+	type Output = Vector3<T>;
+	fn mul(self, rhs: T) -> Vector3<T> {
+		Vector3 { x: self.x * rhs, y: self.y * rhs, z: self.z * rhs }
 	}
 }
 
-impl Mul<Vec3> for Vec3 {
-	type Output = Vec3;
-	fn mul(self, rhs: Vec3) -> Vec3 {
-		Vec3 { x: self.x * rhs.x, y: self.y * rhs.y, z: self.z * rhs.z }
+impl<T: Scalar> Neg for Vector3<T> {
+	type Output = Vector3<T>;
+	fn neg(self) -> Vector3<T> {
+		Vector3 { x: -self.x, y: -self.y, z: -self.z }
 	}
 }
 
-impl Mul<f32> for Vec3 {
-	type Output = Vec3;
-	fn mul(self, rhs: f32) -> Vec3 {
-		Vec3 { x: self.x * rhs, y: self.y * rhs, z: self.z * rhs }
+vec_impls!(Vector3 { x, y, z }, 3, (T, T, T));
+
+impl<T> Index<usize> for Vector3<T> {
+	type Output = T;
+	fn index(&self, i: usize) -> &T {
+		match i {
+			0 => { &self.x },
+			1 => { &self.y },
+			_ => { &self.z }
+		}
 	}
 }
 
-impl Neg for Vec3 {
-	type Output = Vec3;
-	fn neg(self) -> Vec3 {
-		Vec3 { x: -self.x, y: -self.y, z: -self.z }
+impl<T> IndexMut<usize> for Vector3<T> {
+	fn index_mut(&mut self, i: usize) -> &mut T {
+		match i {
+			0 => { &mut self.x },
+			1 => { &mut self.y },
+			_ => { &mut self.z }
+		}
 	}
 }
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct Vec4 { pub x: f32, pub y: f32, pub z: f32, pub w: f32 }
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Vector4<T> { pub x: T, pub y: T, pub z: T, pub w: T }
 
-impl Index<usize> for Vec4 {
-	type Output = f32;
-	fn index(&self, i: usize) -> &f32 {
+impl<T> Index<usize> for Vector4<T> {
+	type Output = T;
+	fn index(&self, i: usize) -> &T {
 		match i {
 			0 => { &self.x },
 			1 => { &self.y },
@@ -192,8 +392,8 @@ impl Index<usize> for Vec4 {
 	}
 }
 
-impl IndexMut<usize> for Vec4 {
-	fn index_mut(&mut self, i: usize) -> &mut f32 {
+impl<T> IndexMut<usize> for Vector4<T> {
+	fn index_mut(&mut self, i: usize) -> &mut T {
 		match i {
 			0 => { &mut self.x },
 			1 => { &mut self.y },
@@ -203,174 +403,182 @@ impl IndexMut<usize> for Vec4 {
 	}
 }
 
-impl Vec4 {
-	pub fn new(x: f32, y: f32, z: f32, w: f32) -> Vec4 {
-		Vec4 { x: x, y: y, z: z, w: w }
+impl<T: Scalar> Vector4<T> {
+	pub fn new(x: T, y: T, z: T, w: T) -> Vector4<T> {
+		Vector4 { x: x, y: y, z: z, w: w }
 	}
 
-	pub fn to_vec3(&self) -> Vec3 {
-		Vec3::new(self.x, self.y, self.z)
+	pub fn to_vec3(&self) -> Vector3<T> {
+		Vector3::new(self.x, self.y, self.z)
 	}
 
-	pub fn from_slice(s: &[f32]) -> Vec4 {
+	pub fn from_slice(s: &[T]) -> Vector4<T> {
 		assert!(s.len() >= 4);
-		Vec4::new(s[0], s[1], s[2], s[3])
+		Vector4::new(s[0], s[1], s[2], s[3])
 	}
 
-	pub fn dot(&self, other: Vec4) -> f32 {
+	pub fn dot(&self, other: Vector4<T>) -> T {
 		self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
 	}
 
-	pub fn length(&self) -> f32 {
-		self.dot(self.clone()).sqrt()
+	pub fn length(&self) -> T {
+		self.dot(*self).sqrt()
 	}
 
-	pub fn normalized(&self) -> Vec4 {
+	pub fn normalized(&self) -> Vector4<T> {
 		let len = self.length();
-		Vec4 { x: self.x / len, y: self.y / len, z: self.z / len, w: self.w / len }
+		Vector4 { x: self.x / len, y: self.y / len, z: self.z / len, w: self.w / len }
 	}
 }
 
-impl Add<Vec4> for Vec4 {
-	type Output = Vec4;
-	fn add(self, rhs: Vec4) -> Vec4 {
-		Vec4 { x: self.x + rhs.x, y: self.y + rhs.y, z: self.z + rhs.z, w: self.w + rhs.w }
+impl<T: Scalar> Add<Vector4<T>> for Vector4<T> {
+	type Output = Vector4<T>;
+	fn add(self, rhs: Vector4<T>) -> Vector4<T> {
+		Vector4 { x: self.x + rhs.x, y: self.y + rhs.y, z: self.z + rhs.z, w: self.w + rhs.w }
 	}
 }
 
-impl Sub<Vec4> for Vec4 {
-	type Output = Vec4;
-	fn sub(self, rhs: Vec4) -> Vec4 {
-		Vec4 { x: self.x - rhs.x, y: self.y - rhs.y, z: self.z - rhs.z, w: self.w - rhs.w }
+impl<T: Scalar> Sub<Vector4<T>> for Vector4<T> {
+	type Output = Vector4<T>;
+	fn sub(self, rhs: Vector4<T>) -> Vector4<T> {
+		Vector4 { x: self.x - rhs.x, y: self.y - rhs.y, z: self.z - rhs.z, w: self.w - rhs.w }
 	}
 }
 
-impl Mul<Vec4> for Vec4 {
-	type Output = Vec4;
-	fn mul(self, rhs: Vec4) -> Vec4 {
-		Vec4 { x: self.x * rhs.x, y: self.y * rhs.y, z: self.z * rhs.z, w: self.w * rhs.w }
+impl<T: Scalar> Mul<Vector4<T>> for Vector4<T> {
+	type Output = Vector4<T>;
+	fn mul(self, rhs: Vector4<T>) -> Vector4<T> {
+		Vector4 { x: self.x * rhs.x, y: self.y * rhs.y, z: self.z * rhs.z, w: self.w * rhs.w }
 	}
 }
 
-impl Mul<f32> for Vec4 {
-	type Output = Vec4;
-	fn mul(self, rhs: f32) -> Vec4 {
-		Vec4 { x: self.x * rhs, y: self.y * rhs, z: self.z * rhs, w: self.w * rhs }
+impl<T: Scalar> Mul<T> for Vector4<T> {
+	type Output = Vector4<T>;
+	fn mul(self, rhs: T) -> Vector4<T> {
+		Vector4 { x: self.x * rhs, y: self.y * rhs, z: self.z * rhs, w: self.w * rhs }
 	}
 }
 
-impl Neg for Vec4 {
-	type Output = Vec4;
-	fn neg(self) -> Vec4 {
-		Vec4 { x: -self.x, y: -self.y, z: -self.z, w: -self.w }
+impl<T: Scalar> Neg for Vector4<T> {
+	type Output = Vector4<T>;
+	fn neg(self) -> Vector4<T> {
+		Vector4 { x: -self.x, y: -self.y, z: -self.z, w: -self.w }
 	}
 }
 
+vec_impls!(Vector4 { x, y, z, w }, 4, (T, T, T, T));
+
 #[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct Mat4 { rows: [Vec4; 4] }
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Matrix4<T> { rows: [Vector4<T>; 4] }
 
-impl Index<usize> for Mat4 {
-	type Output = Vec4;
-	fn index(&self, i: usize) -> &Vec4 {
+impl<T> Index<usize> for Matrix4<T> {
+	type Output = Vector4<T>;
+	fn index(&self, i: usize) -> &Vector4<T> {
 		&self.rows[i]
 	}
 }
 
-impl IndexMut<usize> for Mat4 {
-	fn index_mut(&mut self, i: usize) -> &mut Vec4 {
+impl<T> IndexMut<usize> for Matrix4<T> {
+	fn index_mut(&mut self, i: usize) -> &mut Vector4<T> {
 		&mut self.rows[i]
 	}
 }
 
-impl Mat4 {
-	pub fn new(m: &[f32; 16]) -> Mat4 {
-		Mat4 {
+impl<T: Scalar> Matrix4<T> {
+	pub fn new(m: &[T; 16]) -> Matrix4<T> {
+		Matrix4 {
 			rows: [
-				Vec4::from_slice(&m[0..4]),
-				Vec4::from_slice(&m[4..8]),
-				Vec4::from_slice(&m[8..12]),
-				Vec4::from_slice(&m[12..16])
+				Vector4::from_slice(&m[0..4]),
+				Vector4::from_slice(&m[4..8]),
+				Vector4::from_slice(&m[8..12]),
+				Vector4::from_slice(&m[12..16])
 			]
 		}
 	}
 
-	pub fn from_rows(r0: Vec4, r1: Vec4, r2: Vec4, r3: Vec4) -> Mat4 {
-		Mat4 { rows: [ r0, r1, r2, r3 ] }
+	pub fn from_rows(r0: Vector4<T>, r1: Vector4<T>, r2: Vector4<T>, r3: Vector4<T>) -> Matrix4<T> {
+		Matrix4 { rows: [ r0, r1, r2, r3 ] }
 	}
 
-	pub fn identity() -> Mat4 {
-		Mat4::uniform_scaling(1.0)
+	pub fn identity() -> Matrix4<T> {
+		Matrix4::uniform_scaling(T::one())
 	}
-	
-	pub fn translation(t: Vec3) -> Mat4 {
-		Mat4::new(&[
-			1.0, 0.0, 0.0, t.x,
-			0.0, 1.0, 0.0, t.y,
-			0.0, 0.0, 1.0, t.z,
-			0.0, 0.0, 0.0, 1.0
+
+	pub fn translation(t: Vector3<T>) -> Matrix4<T> {
+		let (o, i) = (T::zero(), T::one());
+		Matrix4::new(&[
+			i, o, o, t.x,
+			o, i, o, t.y,
+			o, o, i, t.z,
+			o, o, o, i
 		])
 	}
 
-	pub fn rotation_x(a: f32) -> Mat4 {
+	pub fn rotation_x(a: T) -> Matrix4<T> {
+		let (o, i) = (T::zero(), T::one());
 		let (s, c) = a.sin_cos();
-		Mat4::new(&[
-			1.0, 0.0, 0.0, 0.0,
-			0.0,   c,  -s, 0.0,
-			0.0,   s,   c, 0.0,
-			0.0, 0.0, 0.0, 1.0
+		Matrix4::new(&[
+			i, o,  o, o,
+			o, c, -s, o,
+			o, s,  c, o,
+			o, o,  o, i
 		])
 	}
 
-	pub fn rotation_y(a: f32) -> Mat4 {
+	pub fn rotation_y(a: T) -> Matrix4<T> {
+		let (o, i) = (T::zero(), T::one());
 		let (s, c) = a.sin_cos();
-		Mat4::new(&[
-			  c, 0.0,  -s, 0.0,
-			0.0, 1.0, 0.0, 0.0,
-			  s, 0.0,   c, 0.0,
-			0.0, 0.0, 0.0, 1.0
+		Matrix4::new(&[
+			 c, o, s, o,
+			 o, i, o, o,
+			-s, o, c, o,
+			 o, o, o, i
 		])
 	}
 
-	pub fn rotation_z(a: f32) -> Mat4 {
+	pub fn rotation_z(a: T) -> Matrix4<T> {
+		let (o, i) = (T::zero(), T::one());
 		let (s, c) = a.sin_cos();
-		Mat4::new(&[
-			  c,  -s, 0.0, 0.0,
-			  s,   c, 0.0, 0.0,
-			0.0, 0.0, 1.0, 0.0,
-			0.0, 0.0, 0.0, 1.0
+		Matrix4::new(&[
+			c, -s, o, o,
+			s,  c, o, o,
+			o,  o, i, o,
+			o,  o, o, i
 		])
 	}
 
-	pub fn axis_angle(axis: Vec3, a: f32) -> Mat4 {
+	pub fn axis_angle(axis: Vector3<T>, a: T) -> Matrix4<T> {
+		let (o, i) = (T::zero(), T::one());
 		let (s, c) = a.sin_cos();
-		let t = 1.0 - c;
+		let t = i - c;
 		let ax = axis.normalized();
 		let x = ax.x;
 		let y = ax.y;
 		let z = ax.z;
-		Mat4::new(&[
-			t * x * x + c, t * x * y - z * s, t * x * z + y * s, 0.0,
-			t * x * y + z * s, t * y * y + c, t * y * z - x * s, 0.0,
-			t * x * z - y * s, t * y * z + x * s, t * z * z + c, 0.0,
-			0.0, 0.0, 0.0, 1.0
+		Matrix4::new(&[
+			t * x * x + c, t * x * y - z * s, t * x * z + y * s, o,
+			t * x * y + z * s, t * y * y + c, t * y * z - x * s, o,
+			t * x * z - y * s, t * y * z + x * s, t * z * z + c, o,
+			o, o, o, i
 		])
 	}
 
-	pub fn scaling(s: Vec3) -> Mat4 {
-		Mat4::new(&[
-			s.x, 0.0, 0.0, 0.0,
-			0.0, s.y, 0.0, 0.0,
-			0.0, 0.0, s.z, 0.0,
-			0.0, 0.0, 0.0, 1.0
+	pub fn scaling(s: Vector3<T>) -> Matrix4<T> {
+		let (o, i) = (T::zero(), T::one());
+		Matrix4::new(&[
+			s.x, o, o, o,
+			o, s.y, o, o,
+			o, o, s.z, o,
+			o, o, o, i
 		])
 	}
 
-	pub fn uniform_scaling(s: f32) -> Mat4 { Mat4::scaling(Vec3::new(s, s, s)) }
+	pub fn uniform_scaling(s: T) -> Matrix4<T> { Matrix4::scaling(Vector3::new(s, s, s)) }
 
-	pub fn transpose(&self) -> Mat4 {
+	pub fn transpose(&self) -> Matrix4<T> {
 		let [a, b, c, d] = self.rows;
-		Mat4::new(&[
+		Matrix4::new(&[
 			a.x, b.x, c.x, d.x,
 			a.y, b.y, c.y, d.y,
 			a.z, b.z, c.z, d.z,
@@ -378,13 +586,13 @@ impl Mat4 {
 		])
 	}
 
-	pub fn inverted(&self) -> Mat4 {
+	pub fn inverted(&self) -> Matrix4<T> {
 		//
 		// Inversion by Cramer's rule.  Code taken from an Intel publication
 		//
-		let mut mat = self.clone();
-		let mut tmp = [0.0f32; 12];
-		let mut src = [0.0f32; 16];
+		let mut mat = *self;
+		let mut tmp = [T::zero(); 12];
+		let mut src = [T::zero(); 16];
 
 		// Transpose
 		for i in 0..4 {
@@ -459,7 +667,7 @@ impl Mat4 {
 		mat[3][3] -= tmp[8] * src[9] + tmp[11] * src[10] + tmp[5] * src[8];
 
 		// Calculate determinant
-		let det = 1.0f32 / (src[0] * mat[0][0] + src[1] * mat[0][1] + src[2] * mat[0][2] + src[3] * mat[0][3]);
+		let det = T::one() / (src[0] * mat[0][0] + src[1] * mat[0][1] + src[2] * mat[0][2] + src[3] * mat[0][3]);
 		for i in 0..4 {
 			for j in 0..4 {
 				mat[i][j] = mat[i][j] * det;
@@ -468,56 +676,84 @@ impl Mat4 {
 		mat
 	}
 
-	pub fn ortho(l: f32, r: f32, b: f32, t: f32, n: f32, f: f32) -> Mat4 {
+	pub fn ortho(l: T, r: T, b: T, t: T, n: T, f: T) -> Matrix4<T> {
+		let (o, i, two) = (T::zero(), T::one(), T::of(2.0));
 		let w = r - l;
 		let h = t - b;
 		let d = f - n;
 
-		Mat4::new(&[
-			2.0 / w,	 0.0,	   0.0, -(r + l) / w,
-				0.0, 2.0 / h,	   0.0, -(t + b) / h,
-				0.0,	 0.0, -2.0 / d, -(f + n) / d,
-				0.0,	 0.0,	   0.0,			 1.0,
+		Matrix4::new(&[
+			two / w,	   o,	     o, -(r + l) / w,
+				  o, two / h,	     o, -(t + b) / h,
+				  o,	   o, -two / d, -(f + n) / d,
+				  o,	   o,	     o,			   i,
 		])
 	}
 
-	pub fn perspective(fov: f32, asp: f32, n: f32, f: f32) -> Mat4 {
-		let cot = 1.0 / (fov / 2.0).tan();
+	pub fn perspective(fov: T, asp: T, n: T, f: T) -> Matrix4<T> {
+		let (o, i, two) = (T::zero(), T::one(), T::of(2.0));
+		let cot = i / (fov / two).tan();
 		let d = n - f;
 
-		Mat4::new(&[
-			cot / asp, 0.0, 		0.0,			   0.0,
-				  0.0, cot,			0.0,			   0.0,
-				  0.0, 0.0,	(f + n) / d, (2.0 * f * n) / d,
-				  0.0, 0.0,		    -1.0,			   0.0
+		Matrix4::new(&[
+			cot / asp,   o, 		  o,			   o,
+					o, cot,			  o,			   o,
+					o,   o, (f + n) / d, (two * f * n) / d,
+					o,   o,		     -i,			   o
 		])
 	}
 
-	pub fn look_at(eye: Vec3, at: Vec3, up: Vec3) -> Mat4 {
+	/// View matrix for a camera at `eye` looking towards `at`, with the
+	/// camera looking down its -Z axis.
+	pub fn look_at(eye: Vector3<T>, at: Vector3<T>, up: Vector3<T>) -> Matrix4<T> {
+		let (o, i) = (T::zero(), T::one());
 		let z = (eye - at).normalized();
 		let x = up.cross(z).normalized();
 		let y = z.cross(x);
 
-		let R = Mat4::new(&[
-			x.x, x.y, -x.z, 0.0,
-			y.x, y.y, -y.z, 0.0,
-			z.x, z.y, -z.z, 0.0,
-			0.0, 0.0, 0.0, 1.0
-		]);
+		Matrix4::new(&[
+			x.x, x.y, x.z, -x.dot(eye),
+			y.x, y.y, y.z, -y.dot(eye),
+			z.x, z.y, z.z, -z.dot(eye),
+			o, o, o, i
+		])
+	}
 
-		Mat4::translation(-eye) * R
+	/// Axis-aligned bounds of the box `min`..`max` once transformed by `self`.
+	pub fn transform_aabb(&self, min: Vector3<T>, max: Vector3<T>) -> (Vector3<T>, Vector3<T>) {
+		let t = Vector3::new(self.rows[0].w, self.rows[1].w, self.rows[2].w);
+		let (mut lo, mut hi) = (t, t);
+		for i in 0..3 {
+			for j in 0..3 {
+				let a = self.rows[i][j] * min[j];
+				let b = self.rows[i][j] * max[j];
+				lo[i] += a.min(b);
+				hi[i] += a.max(b);
+			}
+		}
+		(lo, hi)
 	}
 
-	pub fn as_ptr(&self) -> *const f32 {
+	pub fn as_ptr(&self) -> *const T {
 		&self.rows[0][0]
 	}
 
+	/// True if every element is within `eps` of `o`'s.
+	pub fn approx_eq(&self, o: Matrix4<T>, eps: T) -> bool {
+		(0..4).all(|i| self.rows[i].approx_eq(o.rows[i], eps))
+	}
+
+	/// Converts to another precision, rounding when narrowing.
+	pub fn cast<U: Scalar>(&self) -> Matrix4<U> {
+		Matrix4 { rows: [ self.rows[0].cast(), self.rows[1].cast(), self.rows[2].cast(), self.rows[3].cast() ] }
+	}
+
 }
 
-impl Mul<Mat4> for Mat4 {
-	type Output = Mat4;
-	fn mul(self, rhs: Mat4) -> Mat4 {
-		let mut d = [0.0f32; 16];
+impl<T: Scalar> Mul<Matrix4<T>> for Matrix4<T> {
+	type Output = Matrix4<T>;
+	fn mul(self, rhs: Matrix4<T>) -> Matrix4<T> {
+		let mut d = [T::zero(); 16];
 		let ot = rhs.transpose();
 
 		for j in 0..4 {
@@ -526,14 +762,14 @@ impl Mul<Mat4> for Mat4 {
 			}
 		}
 
-		Mat4::new(&d)
+		Matrix4::new(&d)
 	}
 }
 
-impl Mul<Vec4> for Mat4 {
-	type Output = Vec4;
-	fn mul(self, rhs: Vec4) -> Vec4 {
-		Vec4::new(
+impl<T: Scalar> Mul<Vector4<T>> for Matrix4<T> {
+	type Output = Vector4<T>;
+	fn mul(self, rhs: Vector4<T>) -> Vector4<T> {
+		Vector4::new(
 			self.rows[0].dot(rhs),
 			self.rows[1].dot(rhs),
 			self.rows[2].dot(rhs),
@@ -542,11 +778,11 @@ impl Mul<Vec4> for Mat4 {
 	}
 }
 
-impl Mul<Vec3> for Mat4 {
-	type Output = Vec3;
-	fn mul(self, rhs: Vec3) -> Vec3 {
-		let v = rhs.extend(1.0);
-		Vec3::new(
+impl<T: Scalar> Mul<Vector3<T>> for Matrix4<T> {
+	type Output = Vector3<T>;
+	fn mul(self, rhs: Vector3<T>) -> Vector3<T> {
+		let v = rhs.extend(T::one());
+		Vector3::new(
 			self.rows[0].dot(v),
 			self.rows[1].dot(v),
 			self.rows[2].dot(v)
@@ -554,14 +790,1072 @@ impl Mul<Vec3> for Mat4 {
 	}
 }
 
-impl Mul<f32> for Mat4 {
-	type Output = Mat4;
-	fn mul(self, rhs: f32) -> Mat4 {
-		Mat4::from_rows(
+impl<T: Scalar> Mul<T> for Matrix4<T> {
+	type Output = Matrix4<T>;
+	fn mul(self, rhs: T) -> Matrix4<T> {
+		Matrix4::from_rows(
 			self.rows[0] * rhs,
 			self.rows[1] * rhs,
 			self.rows[2] * rhs,
 			self.rows[3] * rhs
 		)
 	}
-}
\ No newline at end of file
+}
+
+impl<T: Scalar> MulAssign<Matrix4<T>> for Matrix4<T> {
+	fn mul_assign(&mut self, rhs: Matrix4<T>) {
+		*self = *self * rhs;
+	}
+}
+
+impl<T: Scalar> Default for Matrix4<T> {
+	fn default() -> Matrix4<T> { Matrix4::identity() }
+}
+
+impl<T: Scalar> From<[T; 16]> for Matrix4<T> {
+	fn from(m: [T; 16]) -> Matrix4<T> { Matrix4::new(&m) }
+}
+
+impl<T: Scalar> From<[[T; 4]; 4]> for Matrix4<T> {
+	fn from(m: [[T; 4]; 4]) -> Matrix4<T> {
+		Matrix4::from_rows(m[0].into(), m[1].into(), m[2].into(), m[3].into())
+	}
+}
+
+impl<T: Scalar> From<Matrix4<T>> for [[T; 4]; 4] {
+	fn from(m: Matrix4<T>) -> [[T; 4]; 4] {
+		[m.rows[0].into(), m.rows[1].into(), m.rows[2].into(), m.rows[3].into()]
+	}
+}
+
+impl From<Matrix4<f32>> for Matrix4<f64> {
+	fn from(m: Matrix4<f32>) -> Matrix4<f64> { m.cast() }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Matrix2<T> { rows: [Vector2<T>; 2] }
+
+impl<T> Index<usize> for Matrix2<T> {
+	type Output = Vector2<T>;
+	fn index(&self, i: usize) -> &Vector2<T> {
+		&self.rows[i]
+	}
+}
+
+impl<T> IndexMut<usize> for Matrix2<T> {
+	fn index_mut(&mut self, i: usize) -> &mut Vector2<T> {
+		&mut self.rows[i]
+	}
+}
+
+impl<T: Scalar> Matrix2<T> {
+	pub fn new(m: &[T; 4]) -> Matrix2<T> {
+		Matrix2 { rows: [ Vector2::new(m[0], m[1]), Vector2::new(m[2], m[3]) ] }
+	}
+
+	pub fn from_rows(r0: Vector2<T>, r1: Vector2<T>) -> Matrix2<T> {
+		Matrix2 { rows: [ r0, r1 ] }
+	}
+
+	/// Upper-left 2x2 block.
+	pub fn from_mat3(m: Matrix3<T>) -> Matrix2<T> {
+		Matrix2::new(&[
+			m[0].x, m[0].y,
+			m[1].x, m[1].y
+		])
+	}
+
+	pub fn identity() -> Matrix2<T> { Matrix2::scaling(Vector2::new(T::one(), T::one())) }
+
+	pub fn rotation(a: T) -> Matrix2<T> {
+		let (s, c) = a.sin_cos();
+		Matrix2::new(&[
+			c, -s,
+			s,  c
+		])
+	}
+
+	pub fn scaling(s: Vector2<T>) -> Matrix2<T> {
+		let o = T::zero();
+		Matrix2::new(&[
+			s.x, o,
+			o, s.y
+		])
+	}
+
+	pub fn transpose(&self) -> Matrix2<T> {
+		let [a, b] = self.rows;
+		Matrix2::new(&[
+			a.x, b.x,
+			a.y, b.y
+		])
+	}
+
+	pub fn determinant(&self) -> T {
+		self.rows[0].perp_dot(self.rows[1])
+	}
+
+	pub fn inverted(&self) -> Matrix2<T> {
+		let [a, b] = self.rows;
+		let det = T::one() / self.determinant();
+		Matrix2::new(&[
+			 b.y * det, -a.y * det,
+			-b.x * det,  a.x * det
+		])
+	}
+
+	pub fn as_ptr(&self) -> *const T {
+		&self.rows[0].x
+	}
+
+	pub fn approx_eq(&self, o: Matrix2<T>, eps: T) -> bool {
+		self.rows[0].approx_eq(o.rows[0], eps) && self.rows[1].approx_eq(o.rows[1], eps)
+	}
+
+	pub fn cast<U: Scalar>(&self) -> Matrix2<U> {
+		Matrix2 { rows: [ self.rows[0].cast(), self.rows[1].cast() ] }
+	}
+}
+
+impl<T: Scalar> Default for Matrix2<T> {
+	fn default() -> Matrix2<T> { Matrix2::identity() }
+}
+
+impl<T: Scalar> Mul<Matrix2<T>> for Matrix2<T> {
+	type Output = Matrix2<T>;
+	fn mul(self, rhs: Matrix2<T>) -> Matrix2<T> {
+		let ot = rhs.transpose();
+		Matrix2::new(&[
+			self.rows[0].dot(ot.rows[0]), self.rows[0].dot(ot.rows[1]),
+			self.rows[1].dot(ot.rows[0]), self.rows[1].dot(ot.rows[1])
+		])
+	}
+}
+
+impl<T: Scalar> Mul<Vector2<T>> for Matrix2<T> {
+	type Output = Vector2<T>;
+	fn mul(self, rhs: Vector2<T>) -> Vector2<T> {
+		Vector2::new(self.rows[0].dot(rhs), self.rows[1].dot(rhs))
+	}
+}
+
+impl<T: Scalar> Mul<T> for Matrix2<T> {
+	type Output = Matrix2<T>;
+	fn mul(self, rhs: T) -> Matrix2<T> {
+		Matrix2::from_rows(self.rows[0] * rhs, self.rows[1] * rhs)
+	}
+}
+
+impl From<Matrix2<f32>> for Matrix2<f64> {
+	fn from(m: Matrix2<f32>) -> Matrix2<f64> { m.cast() }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Matrix3<T> { rows: [Vector3<T>; 3] }
+
+impl<T> Index<usize> for Matrix3<T> {
+	type Output = Vector3<T>;
+	fn index(&self, i: usize) -> &Vector3<T> {
+		&self.rows[i]
+	}
+}
+
+impl<T> IndexMut<usize> for Matrix3<T> {
+	fn index_mut(&mut self, i: usize) -> &mut Vector3<T> {
+		&mut self.rows[i]
+	}
+}
+
+impl<T: Scalar> Matrix3<T> {
+	pub fn new(m: &[T; 9]) -> Matrix3<T> {
+		Matrix3 {
+			rows: [
+				Vector3::from_slice(&m[0..3]),
+				Vector3::from_slice(&m[3..6]),
+				Vector3::from_slice(&m[6..9])
+			]
+		}
+	}
+
+	pub fn from_rows(r0: Vector3<T>, r1: Vector3<T>, r2: Vector3<T>) -> Matrix3<T> {
+		Matrix3 { rows: [ r0, r1, r2 ] }
+	}
+
+	/// Upper-left 3x3 block, i.e. the rotation and scale without translation.
+	pub fn from_mat4(m: Matrix4<T>) -> Matrix3<T> {
+		Matrix3::from_rows(m[0].to_vec3(), m[1].to_vec3(), m[2].to_vec3())
+	}
+
+	pub fn identity() -> Matrix3<T> { Matrix3::scaling(Vector3::new(T::one(), T::one(), T::one())) }
+
+	pub fn scaling(s: Vector3<T>) -> Matrix3<T> {
+		let o = T::zero();
+		Matrix3::new(&[
+			s.x, o, o,
+			o, s.y, o,
+			o, o, s.z
+		])
+	}
+
+	pub fn transpose(&self) -> Matrix3<T> {
+		let [a, b, c] = self.rows;
+		Matrix3::new(&[
+			a.x, b.x, c.x,
+			a.y, b.y, c.y,
+			a.z, b.z, c.z
+		])
+	}
+
+	pub fn determinant(&self) -> T {
+		let [a, b, c] = self.rows;
+		a.dot(b.cross(c))
+	}
+
+	pub fn inverted(&self) -> Matrix3<T> {
+		// The rows of the inverse's transpose are the cross products of row pairs
+		let [a, b, c] = self.rows;
+		let det = T::one() / self.determinant();
+		Matrix3::from_rows(b.cross(c) * det, c.cross(a) * det, a.cross(b) * det).transpose()
+	}
+
+	/// Transforms normals by the inverse transpose, so non-uniform scale keeps
+	/// them perpendicular to their surfaces.
+	pub fn normal_matrix(model: Matrix4<T>) -> Matrix3<T> {
+		Matrix3::from_mat4(model).inverted().transpose()
+	}
+
+	pub fn as_ptr(&self) -> *const T {
+		&self.rows[0].x
+	}
+
+	pub fn approx_eq(&self, o: Matrix3<T>, eps: T) -> bool {
+		(0..3).all(|i| self.rows[i].approx_eq(o.rows[i], eps))
+	}
+
+	pub fn cast<U: Scalar>(&self) -> Matrix3<U> {
+		Matrix3 { rows: [ self.rows[0].cast(), self.rows[1].cast(), self.rows[2].cast() ] }
+	}
+}
+
+impl<T: Scalar> Default for Matrix3<T> {
+	fn default() -> Matrix3<T> { Matrix3::identity() }
+}
+
+impl<T: Scalar> Mul<Matrix3<T>> for Matrix3<T> {
+	type Output = Matrix3<T>;
+	fn mul(self, rhs: Matrix3<T>) -> Matrix3<T> {
+		let mut d = [T::zero(); 9];
+		let ot = rhs.transpose();
+
+		for j in 0..3 {
+			for i in 0..3 {
+				d[i + j * 3] = self.rows[j].dot(ot.rows[i]);
+			}
+		}
+
+		Matrix3::new(&d)
+	}
+}
+
+impl<T: Scalar> Mul<Vector3<T>> for Matrix3<T> {
+	type Output = Vector3<T>;
+	fn mul(self, rhs: Vector3<T>) -> Vector3<T> {
+		Vector3::new(
+			self.rows[0].dot(rhs),
+			self.rows[1].dot(rhs),
+			self.rows[2].dot(rhs)
+		)
+	}
+}
+
+impl<T: Scalar> Mul<T> for Matrix3<T> {
+	type Output = Matrix3<T>;
+	fn mul(self, rhs: T) -> Matrix3<T> {
+		Matrix3::from_rows(self.rows[0] * rhs, self.rows[1] * rhs, self.rows[2] * rhs)
+	}
+}
+
+impl From<Matrix3<f32>> for Matrix3<f64> {
+	fn from(m: Matrix3<f32>) -> Matrix3<f64> { m.cast() }
+}
+
+/// Rotation quaternion. `w` is the scalar part.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quaternion<T> { pub x: T, pub y: T, pub z: T, pub w: T }
+
+impl<T: Scalar> Quaternion<T> {
+	pub fn new(x: T, y: T, z: T, w: T) -> Quaternion<T> {
+		Quaternion { x: x, y: y, z: z, w: w }
+	}
+
+	pub fn identity() -> Quaternion<T> { Quaternion::new(T::zero(), T::zero(), T::zero(), T::one()) }
+
+	/// Counter-clockwise rotation of `a` radians around `axis`.
+	pub fn axis_angle(axis: Vector3<T>, a: T) -> Quaternion<T> {
+		let (s, c) = (a * T::of(0.5)).sin_cos();
+		let ax = axis.normalized();
+		Quaternion::new(ax.x * s, ax.y * s, ax.z * s, c)
+	}
+
+	pub fn to_axis_angle(&self) -> (Vector3<T>, T) {
+		let (o, i) = (T::zero(), T::one());
+		let q = if self.w < o { -*self } else { *self }.normalized();
+		let s = (i - q.w * q.w).max(o).sqrt();
+		if s < T::of(1e-6) {
+			return (Vector3::new(i, o, o), o);
+		}
+		(Vector3::new(q.x / s, q.y / s, q.z / s), T::of(2.0) * q.w.min(i).acos())
+	}
+
+	/// Yaw around Y, then pitch around X, then roll around Z, all in radians:
+	/// `R = Ry(yaw) * Rx(pitch) * Rz(roll)`.
+	pub fn from_euler(pitch: T, yaw: T, roll: T) -> Quaternion<T> {
+		let (o, i) = (T::zero(), T::one());
+		Quaternion::axis_angle(Vector3::new(o, i, o), yaw) *
+		Quaternion::axis_angle(Vector3::new(i, o, o), pitch) *
+		Quaternion::axis_angle(Vector3::new(o, o, i), roll)
+	}
+
+	/// Inverse of `from_euler`, returned as `(pitch, yaw, roll)`.
+	pub fn to_euler(&self) -> Vector3<T> {
+		let m = self.to_mat4();
+		let sp = -m[1][2];
+		if sp.abs() >= T::of(0.9999) {
+			// Gimbal lock: roll and yaw share an axis, so put it all in yaw
+			let pitch = sp.max(-T::one()).min(T::one()).asin();
+			return Vector3::new(pitch, (-m[2][0]).atan2(m[0][0]), T::zero());
+		}
+		Vector3::new(sp.asin(), m[0][2].atan2(m[2][2]), m[1][0].atan2(m[1][1]))
+	}
+
+	/// Extracts the rotation of `m`, which must not contain scaling.
+	pub fn from_mat4(m: Matrix4<T>) -> Quaternion<T> {
+		let (o, i, two, quarter) = (T::zero(), T::one(), T::of(2.0), T::of(0.25));
+		let trace = m[0][0] + m[1][1] + m[2][2];
+		let q = if trace > o {
+			let s = (trace + i).sqrt() * two;
+			Quaternion::new((m[2][1] - m[1][2]) / s, (m[0][2] - m[2][0]) / s, (m[1][0] - m[0][1]) / s, quarter * s)
+		} else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+			let s = (i + m[0][0] - m[1][1] - m[2][2]).sqrt() * two;
+			Quaternion::new(quarter * s, (m[0][1] + m[1][0]) / s, (m[0][2] + m[2][0]) / s, (m[2][1] - m[1][2]) / s)
+		} else if m[1][1] > m[2][2] {
+			let s = (i + m[1][1] - m[0][0] - m[2][2]).sqrt() * two;
+			Quaternion::new((m[0][1] + m[1][0]) / s, quarter * s, (m[1][2] + m[2][1]) / s, (m[0][2] - m[2][0]) / s)
+		} else {
+			let s = (i + m[2][2] - m[0][0] - m[1][1]).sqrt() * two;
+			Quaternion::new((m[0][2] + m[2][0]) / s, (m[1][2] + m[2][1]) / s, quarter * s, (m[1][0] - m[0][1]) / s)
+		};
+		q.normalized()
+	}
+
+	pub fn to_mat4(&self) -> Matrix4<T> {
+		let (o, i, two) = (T::zero(), T::one(), T::of(2.0));
+		let Quaternion { x, y, z, w } = *self;
+		Matrix4::new(&[
+			i - two * (y * y + z * z), two * (x * y - z * w), two * (x * z + y * w), o,
+			two * (x * y + z * w), i - two * (x * x + z * z), two * (y * z - x * w), o,
+			two * (x * z - y * w), two * (y * z + x * w), i - two * (x * x + y * y), o,
+			o, o, o, i
+		])
+	}
+
+	/// Rotation that turns +Z towards `forward` and keeps +Y as close to `up`
+	/// as possible.
+	pub fn look_rotation(forward: Vector3<T>, up: Vector3<T>) -> Quaternion<T> {
+		let (o, i) = (T::zero(), T::one());
+		let z = forward.normalized();
+		let mut x = up.cross(z);
+		if x.length() < T::of(1e-6) {
+			// `up` is parallel to `forward`, any perpendicular axis will do
+			let alt = if z.x.abs() < T::of(0.9) { Vector3::new(i, o, o) } else { Vector3::new(o, o, i) };
+			x = alt.cross(z);
+		}
+		let x = x.normalized();
+		let y = z.cross(x);
+
+		Quaternion::from_mat4(Matrix4::new(&[
+			x.x, y.x, z.x, o,
+			x.y, y.y, z.y, o,
+			x.z, y.z, z.z, o,
+			o, o, o, i
+		]))
+	}
+
+	/// True if both represent the same rotation within `eps`; `q` and `-q` are equal.
+	pub fn approx_eq(&self, o: Quaternion<T>, eps: T) -> bool {
+		(T::one() - self.normalized().dot(o.normalized()).abs()) <= eps
+	}
+
+	pub fn cast<U: Scalar>(&self) -> Quaternion<U> {
+		Quaternion::new(U::of(self.x.as_f64()), U::of(self.y.as_f64()), U::of(self.z.as_f64()), U::of(self.w.as_f64()))
+	}
+
+	pub fn dot(&self, other: Quaternion<T>) -> T {
+		self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+	}
+
+	pub fn length(&self) -> T {
+		self.dot(*self).sqrt()
+	}
+
+	pub fn normalized(&self) -> Quaternion<T> {
+		let len = self.length();
+		if len <= T::zero() { return Quaternion::identity(); }
+		*self * (T::one() / len)
+	}
+
+	pub fn conjugate(&self) -> Quaternion<T> {
+		Quaternion::new(-self.x, -self.y, -self.z, self.w)
+	}
+
+	pub fn inverted(&self) -> Quaternion<T> {
+		self.conjugate() * (T::one() / self.dot(*self))
+	}
+
+	pub fn rotate(&self, v: Vector3<T>) -> Vector3<T> {
+		let q = Vector3::new(self.x, self.y, self.z);
+		let t = q.cross(v) * T::of(2.0);
+		v + t * self.w + q.cross(t)
+	}
+
+	/// Normalized linear interpolation along the shortest arc. Cheaper than
+	/// `slerp` and close enough for small steps.
+	pub fn nlerp(&self, other: Quaternion<T>, t: T) -> Quaternion<T> {
+		let b = if self.dot(other) < T::zero() { -other } else { other };
+		(*self * (T::one() - t) + b * t).normalized()
+	}
+
+	/// Spherical interpolation along the shortest arc, at constant angular speed.
+	pub fn slerp(&self, other: Quaternion<T>, t: T) -> Quaternion<T> {
+		let mut b = other;
+		let mut cos = self.dot(other);
+		if cos < T::zero() {
+			b = -b;
+			cos = -cos;
+		}
+		if cos > T::of(0.9995) {
+			return self.nlerp(b, t);
+		}
+
+		let theta = cos.acos();
+		let s = theta.sin();
+		(*self * (((T::one() - t) * theta).sin() / s) + b * ((t * theta).sin() / s)).normalized()
+	}
+}
+
+impl<T: Scalar> Mul<Quaternion<T>> for Quaternion<T> {
+	type Output = Quaternion<T>;
+	fn mul(self, rhs: Quaternion<T>) -> Quaternion<T> {
+		Quaternion::new(
+			self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+			self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+			self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+			self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z
+		)
+	}
+}
+
+impl<T: Scalar> Mul<Vector3<T>> for Quaternion<T> {
+	type Output = Vector3<T>;
+	fn mul(self, rhs: Vector3<T>) -> Vector3<T> {
+		self.rotate(rhs)
+	}
+}
+
+impl<T: Scalar> Mul<T> for Quaternion<T> {
+	type Output = Quaternion<T>;
+	fn mul(self, rhs: T) -> Quaternion<T> {
+		Quaternion::new(self.x * rhs, self.y * rhs, self.z * rhs, self.w * rhs)
+	}
+}
+
+impl<T: Scalar> Add<Quaternion<T>> for Quaternion<T> {
+	type Output = Quaternion<T>;
+	fn add(self, rhs: Quaternion<T>) -> Quaternion<T> {
+		Quaternion::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z, self.w + rhs.w)
+	}
+}
+
+impl<T: Scalar> Default for Quaternion<T> {
+	fn default() -> Quaternion<T> { Quaternion::identity() }
+}
+
+impl<T: Scalar> Neg for Quaternion<T> {
+	type Output = Quaternion<T>;
+	fn neg(self) -> Quaternion<T> {
+		Quaternion::new(-self.x, -self.y, -self.z, -self.w)
+	}
+}
+
+impl From<Quaternion<f32>> for Quaternion<f64> {
+	fn from(q: Quaternion<f32>) -> Quaternion<f64> { q.cast() }
+}
+
+/// Cubic Bézier segment from `p0` to `p3`, pulled towards `p1` and `p2`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Bezier<T> { pub p0: Vector3<T>, pub p1: Vector3<T>, pub p2: Vector3<T>, pub p3: Vector3<T> }
+
+impl<T: Scalar> Bezier<T> {
+	pub fn new(p0: Vector3<T>, p1: Vector3<T>, p2: Vector3<T>, p3: Vector3<T>) -> Bezier<T> {
+		Bezier { p0: p0, p1: p1, p2: p2, p3: p3 }
+	}
+
+	/// The part of a uniform Catmull-Rom spline between `p1` and `p2`. Its
+	/// neighbours `p0` and `p3` only shape the tangents.
+	pub fn catmull_rom(p0: Vector3<T>, p1: Vector3<T>, p2: Vector3<T>, p3: Vector3<T>) -> Bezier<T> {
+		let six = T::of(6.0);
+		Bezier::new(p1, p1 + (p2 - p0) / six, p2 - (p3 - p1) / six, p2)
+	}
+
+	pub fn point(&self, t: T) -> Vector3<T> {
+		let three = T::of(3.0);
+		let u = T::one() - t;
+		self.p0 * (u * u * u) + self.p1 * (three * u * u * t) + self.p2 * (three * u * t * t) + self.p3 * (t * t * t)
+	}
+
+	/// Derivative with respect to `t`; not normalized.
+	pub fn tangent(&self, t: T) -> Vector3<T> {
+		let (three, six) = (T::of(3.0), T::of(6.0));
+		let u = T::one() - t;
+		(self.p1 - self.p0) * (three * u * u) + (self.p2 - self.p1) * (six * u * t) + (self.p3 - self.p2) * (three * t * t)
+	}
+
+	/// Arc length, approximated by `steps` chords.
+	pub fn length(&self, steps: usize) -> T {
+		let n = T::of(steps as f64);
+		let mut prev = self.p0;
+		let mut len = T::zero();
+		for i in 1..steps + 1 {
+			let p = self.point(T::of(i as f64) / n);
+			len += (p - prev).length();
+			prev = p;
+		}
+		len
+	}
+}
+
+/// Chords per segment in a `Spline`'s arc-length table.
+const SPLINE_STEPS: usize = 16;
+
+/// A chain of Bézier segments with an arc-length table, so it can be sampled
+/// by distance travelled instead of by curve parameter. The parameter `u`
+/// runs from 0 to the number of segments.
+#[derive(Debug, Clone)]
+pub struct Spline<T> {
+	segments: Vec<Bezier<T>>,
+	/// `(u, distance)` pairs in increasing order
+	table: Vec<(T, T)>
+}
+
+impl<T: Scalar> Spline<T> {
+	pub fn new(segments: Vec<Bezier<T>>) -> Spline<T> {
+		assert!(!segments.is_empty());
+
+		let n = T::of(SPLINE_STEPS as f64);
+		let mut table = vec![(T::zero(), T::zero())];
+		let mut prev = segments[0].p0;
+		let mut dist = T::zero();
+		for (i, seg) in segments.iter().enumerate() {
+			for k in 1..SPLINE_STEPS + 1 {
+				let t = T::of(k as f64) / n;
+				let p = seg.point(t);
+				dist += (p - prev).length();
+				prev = p;
+				table.push((T::of(i as f64) + t, dist));
+			}
+		}
+
+		Spline { segments: segments, table: table }
+	}
+
+	/// A curve through every one of `points`. The ends are extended by
+	/// mirroring, so it leaves the first point heading at the second and
+	/// arrives at the last one from the one before it.
+	pub fn catmull_rom(points: &[Vector3<T>]) -> Spline<T> {
+		assert!(!points.is_empty());
+		if points.len() == 1 {
+			let p = points[0];
+			return Spline::new(vec![Bezier::new(p, p, p, p)]);
+		}
+
+		let two = T::of(2.0);
+		let n = points.len();
+		let first = points[0] * two - points[1];
+		let last = points[n - 1] * two - points[n - 2];
+		let at = |i: isize| -> Vector3<T> {
+			if i < 0 { first } else if i as usize >= n { last } else { points[i as usize] }
+		};
+
+		Spline::new((0..n as isize - 1)
+			.map(|i| Bezier::catmull_rom(at(i - 1), at(i), at(i + 1), at(i + 2)))
+			.collect())
+	}
+
+	pub fn segments(&self) -> &[Bezier<T>] {
+		&self.segments
+	}
+
+	pub fn length(&self) -> T {
+		self.table[self.table.len() - 1].1
+	}
+
+	/// Splits `u` into a segment index and the parameter within it.
+	fn locate(&self, u: T) -> (usize, T) {
+		let last = self.segments.len() - 1;
+		let u = u.max(T::zero());
+		let i = u.floor().to_usize().unwrap_or(0).min(last);
+		(i, (u - T::of(i as f64)).min(T::one()))
+	}
+
+	pub fn point(&self, u: T) -> Vector3<T> {
+		let (i, t) = self.locate(u);
+		self.segments[i].point(t)
+	}
+
+	pub fn tangent(&self, u: T) -> Vector3<T> {
+		let (i, t) = self.locate(u);
+		self.segments[i].tangent(t)
+	}
+
+	/// The parameter `distance` along the curve, clamped to its ends.
+	pub fn param_at(&self, distance: T) -> T {
+		let i = match self.table.binary_search_by(|e| e.1.partial_cmp(&distance).unwrap()) {
+			Ok(i) => return self.table[i].0,
+			Err(i) => i
+		};
+		if i == 0 { return T::zero(); }
+		if i >= self.table.len() { return self.table[self.table.len() - 1].0; }
+
+		let (u0, d0) = self.table[i - 1];
+		let (u1, d1) = self.table[i];
+		u0 + (u1 - u0) * (distance - d0) / (d1 - d0)
+	}
+
+	pub fn point_at(&self, distance: T) -> Vector3<T> {
+		self.point(self.param_at(distance))
+	}
+
+	pub fn tangent_at(&self, distance: T) -> Vector3<T> {
+		self.tangent(self.param_at(distance))
+	}
+}
+
+/// The plane `normal · p + d = 0`. Points with a positive distance are in
+/// front of it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Plane<T> { pub normal: Vector3<T>, pub d: T }
+
+impl<T: Scalar> Plane<T> {
+	pub fn new(normal: Vector3<T>, d: T) -> Plane<T> {
+		Plane { normal: normal, d: d }
+	}
+
+	/// Builds a plane from `(a, b, c, d)` coefficients, normalizing them so
+	/// `distance` is in world units.
+	pub fn from_vec4(v: Vector4<T>) -> Plane<T> {
+		let len = v.to_vec3().length();
+		Plane::new(v.to_vec3() / len, v.w / len)
+	}
+
+	pub fn distance(&self, p: Vector3<T>) -> T {
+		self.normal.dot(p) + self.d
+	}
+}
+
+/// The six planes bounding a view volume, facing inwards.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frustum<T> { pub planes: [Plane<T>; 6] }
+
+impl<T: Scalar> Frustum<T> {
+	/// Extracts the planes of `projection * view`. Anything inside ends up
+	/// within the clip-space cube.
+	pub fn from_matrix(m: Matrix4<T>) -> Frustum<T> {
+		let [r0, r1, r2, r3] = m.rows;
+		Frustum {
+			planes: [
+				Plane::from_vec4(r3 + r0), // left
+				Plane::from_vec4(r3 - r0), // right
+				Plane::from_vec4(r3 + r1), // bottom
+				Plane::from_vec4(r3 - r1), // top
+				Plane::from_vec4(r3 + r2), // near
+				Plane::from_vec4(r3 - r2)  // far
+			]
+		}
+	}
+
+	pub fn contains_point(&self, p: Vector3<T>) -> bool {
+		self.planes.iter().all(|pl| pl.distance(p) >= T::zero())
+	}
+
+	pub fn intersects_sphere(&self, center: Vector3<T>, radius: T) -> bool {
+		self.planes.iter().all(|pl| pl.distance(center) >= -radius)
+	}
+
+	/// Conservative: boxes near the frustum's corners may pass even if they
+	/// are just outside, but nothing inside is ever rejected.
+	pub fn intersects_aabb(&self, min: Vector3<T>, max: Vector3<T>) -> bool {
+		self.planes.iter().all(|pl| {
+			// The corner furthest along the plane's normal
+			let n = pl.normal;
+			let p = Vector3::new(
+				if n.x >= T::zero() { max.x } else { min.x },
+				if n.y >= T::zero() { max.y } else { min.y },
+				if n.z >= T::zero() { max.z } else { min.z }
+			);
+			pl.distance(p) >= T::zero()
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate rand;
+
+	use std::f32::consts::PI;
+	use self::rand::{ Rng, SeedableRng, StdRng };
+	use super::*;
+
+	const EPS: f32 = 1e-4;
+	const RUNS: usize = 500;
+
+	fn rng() -> StdRng {
+		// Fixed seed so a failure can be reproduced
+		StdRng::from_seed(&[0x5eed][..])
+	}
+
+	fn rand_vec3(rng: &mut StdRng, r: f32) -> Vec3 {
+		Vec3::new(rng.gen_range(-r, r), rng.gen_range(-r, r), rng.gen_range(-r, r))
+	}
+
+	fn rand_axis(rng: &mut StdRng) -> Vec3 {
+		loop {
+			let v = rand_vec3(rng, 1.0);
+			if v.length() > 0.1 { return v.normalized(); }
+		}
+	}
+
+	fn rand_angle(rng: &mut StdRng) -> f32 {
+		rng.gen_range(-PI, PI)
+	}
+
+	/// A random translate * rotate * scale, like the model matrices we draw with.
+	fn rand_affine(rng: &mut StdRng) -> Mat4 {
+		let s = Vec3::new(rng.gen_range(0.2, 5.0), rng.gen_range(0.2, 5.0), rng.gen_range(0.2, 5.0));
+		Mat4::translation(rand_vec3(rng, 50.0)) * Mat4::axis_angle(rand_axis(rng), rand_angle(rng)) * Mat4::scaling(s)
+	}
+
+	/// A camera somewhere around the origin looking at a nearby point.
+	fn rand_view(rng: &mut StdRng) -> (Vec3, Vec3, Mat4) {
+		let eye = rand_vec3(rng, 20.0);
+		let at = eye + rand_axis(rng) * rng.gen_range(1.0, 10.0);
+		(eye, at, Mat4::look_at(eye, at, Vec3::new(0.0, 1.0, 0.0)))
+	}
+
+	fn assert_vec3(a: Vec3, b: Vec3, eps: f32) {
+		assert!(a.approx_eq(b, eps), "{:?} != {:?}", a, b);
+	}
+
+	fn assert_mat4(a: Mat4, b: Mat4, eps: f32) {
+		assert!(a.approx_eq(b, eps), "{:?} != {:?}", a, b);
+	}
+
+	#[test]
+	fn inverse_of_affine_is_identity() {
+		let mut rng = rng();
+		for _ in 0..RUNS {
+			let m = rand_affine(&mut rng);
+			assert_mat4(m * m.inverted(), Mat4::identity(), EPS);
+			assert_mat4(m.inverted() * m, Mat4::identity(), EPS);
+		}
+	}
+
+	#[test]
+	fn inverse_of_view_projection_is_identity() {
+		let mut rng = rng();
+		for _ in 0..RUNS {
+			let (_, _, view) = rand_view(&mut rng);
+			let proj = Mat4::perspective(rng.gen_range(0.3, 2.0), rng.gen_range(0.5, 2.5), 0.1, 100.0);
+			let m = proj * view;
+			assert_mat4(m * m.inverted(), Mat4::identity(), EPS);
+		}
+	}
+
+	#[test]
+	fn inverse_golden() {
+		let m = Mat4::new(&[
+			2.0, 0.0, 0.0, 1.0,
+			0.0, 4.0, 0.0, 2.0,
+			0.0, 0.0, 0.5, 3.0,
+			0.0, 0.0, 0.0, 1.0
+		]);
+		assert_mat4(m.inverted(), Mat4::new(&[
+			0.5,  0.0, 0.0, -0.5,
+			0.0, 0.25, 0.0, -0.5,
+			0.0,  0.0, 2.0, -6.0,
+			0.0,  0.0, 0.0,  1.0
+		]), 1e-6);
+	}
+
+	#[test]
+	fn project_unproject_round_trip() {
+		let mut rng = rng();
+		for _ in 0..RUNS {
+			let (_, _, view) = rand_view(&mut rng);
+			let proj = Mat4::perspective(rng.gen_range(0.3, 2.0), rng.gen_range(0.5, 2.5), 0.5, 200.0);
+			let viewport = Vec4::new(rng.gen_range(0.0, 100.0), rng.gen_range(0.0, 100.0), rng.gen_range(200.0, 2000.0), rng.gen_range(200.0, 2000.0));
+
+			let win = Vec3::new(
+				viewport.x + rng.gen_range(0.0, viewport.z),
+				viewport.y + rng.gen_range(0.0, viewport.w),
+				rng.gen_range(0.0, 0.99));
+			let back = win.unproject(viewport, view, proj).project(viewport, view, proj);
+			// Within a tenth of a pixel; depth is much more sensitive far from the camera
+			assert_vec3(Vec3::new(back.x, back.y, 0.0), Vec3::new(win.x, win.y, 0.0), 0.1);
+			assert!((back.z - win.z).abs() < 1e-3, "{} != {}", back.z, win.z);
+		}
+	}
+
+	#[test]
+	fn project_golden() {
+		let view = Mat4::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::zero(), Vec3::new(0.0, 1.0, 0.0));
+		let proj = Mat4::perspective(PI / 2.0, 2.0, 1.0, 10.0);
+		let viewport = Vec4::new(10.0, 20.0, 800.0, 400.0);
+
+		// The point being looked at lands in the middle of the viewport
+		let c = Vec3::zero().project(viewport, view, proj);
+		assert_vec3(Vec3::new(c.x, c.y, 0.0), Vec3::new(410.0, 220.0, 0.0), EPS);
+
+		// Top left corner of the near plane, one unit in front of the eye
+		let tl = Vec3::new(-2.0, 1.0, 4.0).project(viewport, view, proj);
+		assert_vec3(tl, Vec3::new(10.0, 20.0, 0.0), EPS);
+	}
+
+	#[test]
+	fn look_at_is_orthonormal() {
+		let mut rng = rng();
+		for _ in 0..RUNS {
+			let (eye, at, view) = rand_view(&mut rng);
+			let r = Mat3::from_mat4(view);
+			assert!((r * r.transpose()).approx_eq(Mat3::identity(), EPS), "{:?}", r);
+			assert!((r.determinant() - 1.0).abs() < EPS);
+
+			// The eye goes to the origin and the target straight down -Z
+			assert_vec3(view * eye, Vec3::zero(), 1e-3);
+			assert_vec3(view * at, Vec3::new(0.0, 0.0, -(at - eye).length()), 1e-3);
+		}
+	}
+
+	#[test]
+	fn look_at_golden() {
+		let up = Vec3::new(0.0, 1.0, 0.0);
+		assert_mat4(
+			Mat4::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::zero(), up),
+			Mat4::translation(Vec3::new(0.0, 0.0, -5.0)), 1e-6);
+
+		// Looking down -X from +X, world -Z is to the right
+		let view = Mat4::look_at(Vec3::new(5.0, 0.0, 0.0), Vec3::zero(), up);
+		assert_vec3(view * Vec3::new(0.0, 0.0, -1.0), Vec3::new(1.0, 0.0, -5.0), 1e-6);
+		assert_vec3(view * Vec3::new(0.0, 2.0, 0.0), Vec3::new(0.0, 2.0, -5.0), 1e-6);
+	}
+
+	#[test]
+	fn rotations_compose() {
+		let mut rng = rng();
+		let axes: [(fn(f32) -> Mat4, Vec3); 3] = [
+			(Mat4::rotation_x, Vec3::new(1.0, 0.0, 0.0)),
+			(Mat4::rotation_y, Vec3::new(0.0, 1.0, 0.0)),
+			(Mat4::rotation_z, Vec3::new(0.0, 0.0, 1.0))
+		];
+
+		for _ in 0..RUNS {
+			let a = rand_angle(&mut rng);
+			let b = rand_angle(&mut rng);
+			for &(rot, axis) in axes.iter() {
+				assert_mat4(rot(a) * rot(b), rot(a + b), EPS);
+				assert_mat4(rot(a), Mat4::axis_angle(axis, a), EPS);
+				assert_mat4(rot(a) * rot(-a), Mat4::identity(), EPS);
+			}
+
+			let axis = rand_axis(&mut rng);
+			assert_mat4(Mat4::axis_angle(axis, a) * Mat4::axis_angle(axis, b), Mat4::axis_angle(axis, a + b), EPS);
+			assert_mat4(Mat4::axis_angle(axis, a).inverted(), Mat4::axis_angle(axis, a).transpose(), EPS);
+		}
+	}
+
+	#[test]
+	fn rotations_are_right_handed() {
+		let x = Vec3::new(1.0, 0.0, 0.0);
+		let y = Vec3::new(0.0, 1.0, 0.0);
+		let z = Vec3::new(0.0, 0.0, 1.0);
+		assert_vec3(Mat4::rotation_x(PI / 2.0) * y, z, 1e-6);
+		assert_vec3(Mat4::rotation_y(PI / 2.0) * z, x, 1e-6);
+		assert_vec3(Mat4::rotation_z(PI / 2.0) * x, y, 1e-6);
+	}
+
+	#[test]
+	fn quat_matches_matrices() {
+		let mut rng = rng();
+		for _ in 0..RUNS {
+			let (a1, a2) = (rand_axis(&mut rng), rand_axis(&mut rng));
+			let (t1, t2) = (rand_angle(&mut rng), rand_angle(&mut rng));
+			let q1 = Quat::axis_angle(a1, t1);
+			let q2 = Quat::axis_angle(a2, t2);
+			let v = rand_vec3(&mut rng, 10.0);
+
+			assert_mat4(q1.to_mat4(), Mat4::axis_angle(a1, t1), EPS);
+			assert_mat4((q1 * q2).to_mat4(), Mat4::axis_angle(a1, t1) * Mat4::axis_angle(a2, t2), EPS);
+			assert_vec3(q1 * v, Mat4::axis_angle(a1, t1) * v, 1e-3);
+			assert!(Quat::from_mat4(q1.to_mat4()).approx_eq(q1, EPS));
+		}
+	}
+
+	#[test]
+	fn ortho_maps_corners_to_ndc() {
+		let mut rng = rng();
+		for _ in 0..RUNS {
+			let (l, b, n) = (rng.gen_range(-100.0, 100.0), rng.gen_range(-100.0, 100.0), rng.gen_range(-10.0, 10.0));
+			let (r, t, f) = (l + rng.gen_range(1.0, 100.0), b + rng.gen_range(1.0, 100.0), n + rng.gen_range(1.0, 100.0));
+			let m = Mat4::ortho(l, r, b, t, n, f);
+
+			// The camera looks down -Z, so the near plane is at z = -n
+			for &(x, nx) in [(l, -1.0), (r, 1.0)].iter() {
+				for &(y, ny) in [(b, -1.0), (t, 1.0)].iter() {
+					for &(z, nz) in [(-n, -1.0), (-f, 1.0)].iter() {
+						assert_vec3(m * Vec3::new(x, y, z), Vec3::new(nx, ny, nz), EPS);
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn perspective_maps_frustum_to_ndc() {
+		let mut rng = rng();
+		for _ in 0..RUNS {
+			let fov = rng.gen_range(0.3, 2.5);
+			let asp = rng.gen_range(0.5, 2.5);
+			let n = rng.gen_range(0.1, 2.0);
+			let f = n + rng.gen_range(1.0, 500.0);
+			let m = Mat4::perspective(fov, asp, n, f);
+
+			for &(d, nz) in [(n, -1.0), (f, 1.0)].iter() {
+				let h = d * (fov / 2.0).tan();
+				let clip = m * Vec4::new(h * asp, -h, -d, 1.0);
+				assert_vec3(clip.to_vec3() / clip.w, Vec3::new(1.0, -1.0, nz), 1e-3);
+			}
+		}
+	}
+	#[test]
+	fn precision_conversions() {
+		let v = Vec3::new(1.5, -2.25, 1e6);
+		let d: DVec3 = v.into();
+		assert_eq!(d.cast::<f32>(), v);
+
+		// Far from the origin f32 can no longer tell nearby points apart
+		let far = DVec3::new(1e8, 0.0, 0.0);
+		let near = far + DVec3::new(0.5, 0.0, 0.0);
+		assert_eq!(near.distance(far), 0.5);
+		assert_eq!(near.cast::<f32>(), far.cast::<f32>());
+
+		let m = DMat4::translation(far) * DMat4::rotation_y(1.0);
+		assert!((m * m.inverted()).approx_eq(DMat4::identity(), 1e-6));
+		assert!(Quat::axis_angle(Vec3::new(0.0, 1.0, 0.0), 1.0).cast::<f64>().approx_eq(DQuat::from_mat4(DMat4::rotation_y(1.0)), 1e-7));
+	}
+	#[test]
+	fn catmull_rom_passes_through_points() {
+		let mut rng = rng();
+		for _ in 0..RUNS / 10 {
+			let points: Vec<Vec3> = (0..rng.gen_range(2, 10)).map(|_| rand_vec3(&mut rng, 10.0)).collect();
+			let spline = Spline::catmull_rom(&points);
+			assert_eq!(spline.segments().len(), points.len() - 1);
+			for (i, &p) in points.iter().enumerate() {
+				assert_vec3(spline.point(i as f32), p, EPS);
+			}
+		}
+	}
+
+	#[test]
+	fn spline_samples_by_distance() {
+		// Straight lines are parameterised by length exactly
+		let a = Vec3::new(1.0, 2.0, 3.0);
+		let line = Spline::catmull_rom(&[a, a + Vec3::new(3.0, 0.0, 4.0), a + Vec3::new(6.0, 0.0, 8.0)]);
+		assert!((line.length() - 10.0).abs() < EPS);
+		assert_vec3(line.point_at(2.5), a + Vec3::new(1.5, 0.0, 2.0), 1e-3);
+		assert_vec3(line.point_at(-1.0), a, EPS);
+		assert_vec3(line.point_at(11.0), a + Vec3::new(6.0, 0.0, 8.0), EPS);
+
+		// Equal distance steps cover equal lengths of a curve, whatever the parameter does
+		let (k, r) = (0.5523, 2.0);
+		let arc = Spline::new(vec![Bezier::new(
+			Vec3::new(r, 0.0, 0.0), Vec3::new(r, 0.0, r * k),
+			Vec3::new(r * k, 0.0, r), Vec3::new(0.0, 0.0, r))]);
+		assert!((arc.length() - PI * r / 2.0).abs() < 1e-2);
+
+		let step = arc.length() / 20.0;
+		for i in 0..20 {
+			let (d0, d1) = (step * i as f32, step * (i + 1) as f32);
+			let chord = (arc.point_at(d1) - arc.point_at(d0)).length();
+			assert!((chord - step).abs() < 1e-2 * step, "{} != {}", chord, step);
+		}
+	}
+	#[test]
+	fn frustum_agrees_with_projection() {
+		let mut rng = rng();
+		for _ in 0..RUNS {
+			let (_, _, view) = rand_view(&mut rng);
+			let proj = Mat4::perspective(rng.gen_range(0.3, 2.0), rng.gen_range(0.5, 2.5), 0.5, 50.0);
+			let m = proj * view;
+			let frustum = Frustum::from_matrix(m);
+
+			let p = rand_vec3(&mut rng, 40.0);
+			let clip = m * p.extend(1.0);
+			let ndc = clip.to_vec3() / clip.w;
+			let inside = clip.w > 0.0 && ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0 && ndc.z.abs() <= 1.0;
+			// Skip points right on the boundary, where rounding decides
+			let margin = ndc.x.abs().max(ndc.y.abs()).max(ndc.z.abs());
+			if clip.w > 0.0 && (margin - 1.0).abs() < 1e-3 { continue; }
+			assert_eq!(frustum.contains_point(p), inside, "{:?} -> {:?}", p, ndc);
+
+			// A point and any sphere or box around it can't be culled
+			if inside {
+				assert!(frustum.intersects_sphere(p, 0.1));
+				assert!(frustum.intersects_aabb(p - Vec3::new(0.1, 0.2, 0.3), p + Vec3::new(0.3, 0.2, 0.1)));
+			}
+		}
+	}
+
+	#[test]
+	fn frustum_golden() {
+		let frustum = Frustum::from_matrix(Mat4::ortho(-1.0, 1.0, -1.0, 1.0, 0.0, 10.0));
+		let (min, max) = (Vec3::new(-0.5, -0.5, -0.5), Vec3::new(0.5, 0.5, 0.5));
+
+		assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -5.0)));
+		assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 5.0)));
+		assert!(frustum.intersects_sphere(Vec3::new(1.4, 0.0, -5.0), 0.5));
+		assert!(!frustum.intersects_sphere(Vec3::new(1.6, 0.0, -5.0), 0.5));
+		assert!(frustum.intersects_aabb(min + Vec3::new(1.4, 0.0, -5.0), max + Vec3::new(1.4, 0.0, -5.0)));
+		assert!(!frustum.intersects_aabb(min + Vec3::new(1.6, 0.0, -5.0), max + Vec3::new(1.6, 0.0, -5.0)));
+		assert!(!frustum.intersects_aabb(min + Vec3::new(0.0, 0.0, -11.0), max + Vec3::new(0.0, 0.0, -11.0)));
+	}
+
+	#[test]
+	fn transformed_aabb_contains_corners() {
+		let mut rng = rng();
+		for _ in 0..RUNS / 10 {
+			let m = rand_affine(&mut rng);
+			let (min, max) = (Vec3::new(-1.0, 0.0, -2.0), Vec3::new(1.0, 0.5, 3.0));
+			let (lo, hi) = m.transform_aabb(min, max);
+			for i in 0..8 {
+				let c = Vec3::new(
+					if i & 1 == 0 { min.x } else { max.x },
+					if i & 2 == 0 { min.y } else { max.y },
+					if i & 4 == 0 { min.z } else { max.z });
+				let p = m * c;
+				assert!(p.approx_eq(p.max(lo).min(hi), 1e-3), "{:?} outside {:?}..{:?}", p, lo, hi);
+			}
+		}
+	}
+}